@@ -0,0 +1,134 @@
+//! Runs the picker in a normal terminal, outside of zellij, choosing from
+//! the lines read on stdin and printing the chosen line to stdout:
+//!
+//! ```sh
+//! ls | cargo run --target x86_64-unknown-linux-gnu --example headless \
+//!     --features headless -- nucleo_theme=monochrome nucleo_keymap=vim
+//! ```
+//!
+//! (The target needs to be given explicitly, since this crate builds for
+//! wasm by default. Use your own host's target triple if it differs.)
+//!
+//! Arguments of the form `key=value` are passed to the picker as plugin
+//! configuration, so this can be used to try out matching options, themes
+//! and keymaps without building and reloading a plugin. Zellij's timers are
+//! emulated by sending the picker a timer event whenever no input arrives
+//! for a tenth of a second, so timer-based behavior works but may fire up
+//! to that much later than it would in zellij. Mouse input isn't read from
+//! the terminal, so the mouse options have no effect.
+
+use std::io::{BufRead as _, Read as _, Write as _};
+
+fn stty(tty: &std::fs::File, args: &[&str]) -> std::io::Result<String> {
+    let output = std::process::Command::new("stty")
+        .args(args)
+        .stdin(tty.try_clone()?)
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other("stty failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn main() -> std::io::Result<()> {
+    let configuration: std::collections::BTreeMap<_, _> = std::env::args()
+        .skip(1)
+        .filter_map(|arg| {
+            let (key, value) = arg.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect();
+    let config = match zellij_nucleo::PickerConfig::parse(&configuration) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut picker = zellij_nucleo::Picker::<String>::default();
+    picker.configure(config);
+    picker.extend(
+        std::io::stdin()
+            .lock()
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.is_empty())
+            .map(|line| zellij_nucleo::Entry::new(line.clone(), line)),
+    );
+
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")?;
+    let saved = stty(&tty, &["-g"])?;
+    // return from reads after a tenth of a second without input, so that
+    // timer events can be delivered
+    stty(&tty, &["raw", "-echo", "min", "0", "time", "1"])?;
+    write!(tty, "\x1b[?1049h\x1b[?25l")?;
+
+    let chosen = run(&mut picker, &mut tty);
+
+    write!(tty, "\x1b[?25h\x1b[?1049l")?;
+    stty(&tty, &[&saved])?;
+
+    match chosen? {
+        Some(line) => {
+            println!("{line}");
+            Ok(())
+        }
+        None => std::process::exit(1),
+    }
+}
+
+fn run(
+    picker: &mut zellij_nucleo::Picker<String>,
+    tty: &mut std::fs::File,
+) -> std::io::Result<Option<String>> {
+    let mut buf = [0; 256];
+    let mut drawn_size = None;
+    loop {
+        let size = stty(tty, &["size"])?;
+        let (rows, cols) = size
+            .split_once(' ')
+            .and_then(|(rows, cols)| {
+                Some((rows.parse().ok()?, cols.parse().ok()?))
+            })
+            .filter(|&(rows, cols)| rows > 0 && cols > 0)
+            .unwrap_or((24, 80));
+        if picker.needs_redraw() || drawn_size != Some((rows, cols)) {
+            let screen = picker.render_to_string(rows, cols);
+            write!(tty, "\x1b[H\x1b[2J{}", screen.replace('\n', "\r\n"))?;
+            tty.flush()?;
+            drawn_size = Some((rows, cols));
+        }
+
+        let len = tty.read(&mut buf)?;
+        let events = if len == 0 {
+            vec![zellij_tile::prelude::Event::Timer(0.1)]
+        } else {
+            zellij_nucleo::testing::parse_terminal_input(&buf[..len])
+        };
+        for response in zellij_nucleo::testing::send(picker, events) {
+            match response {
+                zellij_nucleo::Response::Select { index, .. } => {
+                    return Ok(Some(picker.entries()[index].data.clone()));
+                }
+                zellij_nucleo::Response::SelectMulti(indices) => {
+                    return Ok(Some(
+                        indices
+                            .into_iter()
+                            .map(|index| picker.entries()[index].data.clone())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ));
+                }
+                zellij_nucleo::Response::Query(query) => {
+                    return Ok(Some(query));
+                }
+                zellij_nucleo::Response::Cancel => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
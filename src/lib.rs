@@ -35,21 +35,24 @@
 //!
 //!     fn update(&mut self, event: Event) -> bool {
 //!         match self.picker.update(&event) {
-//!             Some(zellij_nucleo::Response::Select(entry)) => {
-//!                 go_to_tab(entry.data);
+//!             Some(zellij_nucleo::Response::Select { index, .. }) => {
+//!                 go_to_tab(self.picker.entries()[index].data);
 //!                 close_self();
 //!             }
 //!             Some(zellij_nucleo::Response::Cancel) => {
 //!                 close_self();
 //!             }
-//!             None => {}
+//!             _ => {}
 //!         }
 //!
 //!         if let Event::TabUpdate(tabs) = event {
 //!             self.picker.clear();
-//!             self.picker.extend(tabs.iter().map(|tab| zellij_nucleo::Entry {
-//!                 data: u32::try_from(tab.position).unwrap(),
-//!                 string: format!("{}: {}", tab.position + 1, tab.name),
+//!             self.picker.extend(tabs.iter().map(|tab| {
+//!                 zellij_nucleo::Entry::new(
+//!                     format!("{}: {}", tab.position + 1, tab.name),
+//!                     u32::try_from(tab.position).unwrap(),
+//!                 )
+//!                 .with_current(tab.active)
 //!             }));
 //!         }
 //!
@@ -61,15 +64,101 @@
 //!     }
 //! }
 //! ```
+//!
+//! ## Custom rendering
+//!
+//! Plugins which want to draw the list themselves can still use the picker
+//! for input handling and matching, by calling [`Picker::update`] as usual
+//! but using [`Picker::results`], [`Picker::selected_result`] and
+//! [`Picker::scroll_window`] in place of [`Picker::render`]. Plugins which
+//! only want to draw additional information around the picker can use a
+//! [`Layout`] instead.
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "mouse"))]
+use host::unsubscribe;
+#[cfg(not(target_arch = "wasm32"))]
+use host::{
+    cli_pipe_output, run_command, set_timeout, subscribe,
+    unblock_cli_pipe_input,
+};
+#[cfg(all(not(target_arch = "wasm32"), feature = "helpers"))]
+use host::{
+    delete_dead_session, new_tabs_with_layout, new_tabs_with_layout_info,
+    open_terminal, open_terminal_floating, switch_session,
+};
 use zellij_tile::prelude::*;
 
 use std::fmt::Write as _;
 
-use owo_colors::OwoColorize as _;
-use unicode_width::UnicodeWidthChar as _;
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr as _};
 
 const PICKER_EVENTS: &[EventType] = &[EventType::Key];
+const HISTORY_LEN: usize = 100;
+const JUMPLIST_LEN: usize = 100;
+#[cfg(feature = "helpers")]
+const DIRECTORIES_LEN: usize = 200;
+#[cfg(feature = "helpers")]
+const DIRECTORIES_KEY: &str = "directories";
+#[cfg(feature = "helpers")]
+const BOOKMARKS_KEY: &str = "bookmarks";
+#[cfg(feature = "mouse")]
+const DOUBLE_CLICK_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_millis(500);
+const COMMAND_CONTEXT_KEY: &str = "zellij_nucleo_command";
+const COMMAND_SOURCE_CONTEXT_KEY: &str = "zellij_nucleo_command_source";
+static COMMAND_SOURCES: std::sync::atomic::AtomicU64 =
+    std::sync::atomic::AtomicU64::new(0);
+const DOUBLE_ESC_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_millis(750);
+
+// the zellij plugin api can only be called from inside zellij, so when the
+// picker is built for anything other than a plugin (tests, the headless
+// example) calls into the host do nothing. these shadow the functions from
+// the zellij_tile prelude with the same signatures.
+#[cfg(not(target_arch = "wasm32"))]
+mod host {
+    use zellij_tile::prelude::*;
+
+    pub fn subscribe(_event_types: &[EventType]) {}
+
+    #[cfg(feature = "mouse")]
+    pub fn unsubscribe(_event_types: &[EventType]) {}
+
+    pub fn set_timeout(_secs: f64) {}
+
+    pub fn run_command(
+        _cmd: &[&str],
+        _context: std::collections::BTreeMap<String, String>,
+    ) {
+    }
+
+    pub fn unblock_cli_pipe_input(_pipe_name: &str) {}
+
+    pub fn cli_pipe_output(_pipe_name: &str, _output: &str) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn switch_session(_name: Option<&str>) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn delete_dead_session(_name: &str) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn new_tabs_with_layout(_layout: &str) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn new_tabs_with_layout_info(_layout_info: LayoutInfo) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn open_terminal<P: AsRef<std::path::Path>>(_path: P) {}
+
+    #[cfg(feature = "helpers")]
+    pub fn open_terminal_floating<P: AsRef<std::path::Path>>(
+        _path: P,
+        _coordinates: Option<FloatingPaneCoordinates>,
+    ) {
+    }
+}
 
 /// An entry in the picker.
 ///
@@ -78,11 +167,105 @@ const PICKER_EVENTS: &[EventType] = &[EventType::Key];
 #[derive(Debug)]
 pub struct Entry<T> {
     /// String that will be displayed in the picker window, and filtered when
-    /// searching.
-    pub string: String,
+    /// searching. This can borrow a `&'static str`, so that fixed lists of
+    /// entries don't need to copy their labels.
+    pub string: std::borrow::Cow<'static, str>,
     /// Extra data associated with the picker entry, which can be retrieved
     /// when an entry is selected.
     pub data: T,
+    /// Whether this entry represents the thing that is currently active
+    /// (for instance, the focused tab in a tab switcher). Current entries
+    /// are rendered with a marker, and can be moved to the end of the list
+    /// or hidden entirely (see [`Picker::set_current_entry`]).
+    pub current: bool,
+    /// Extra information about this entry, shown at the bottom of the
+    /// picker while the entry is selected.
+    pub tooltip: Option<String>,
+    /// Text to display in place of [`string`](Self::string). Searching
+    /// still matches against `string`, so matched characters aren't
+    /// highlighted when this is set.
+    pub display: Option<String>,
+    /// The name of the section this entry is listed under. Search results
+    /// are sorted within their group, groups are listed in the order they
+    /// were first added, and each group is shown under a header with its
+    /// name. Entries without a group are listed before all groups.
+    pub group: Option<String>,
+    /// A key binding for the action this entry represents (such as
+    /// `Ctrl p`), displayed dimmed at the right edge of the entry so that
+    /// users can learn the shortcut. This is only displayed, and isn't
+    /// matched when searching.
+    pub keybinding: Option<String>,
+    /// A tag identifying where this entry came from, for pickers which
+    /// combine entries from several places (such as other plugins, see
+    /// [`Picker::handle_pipe`]).
+    pub source: Option<String>,
+    /// Whether this entry is listed regardless of the query, for entries
+    /// such as "Create new…" which should always be available. These
+    /// entries aren't matched or scored, and are listed after the entries
+    /// which match the query (within their group, if any).
+    pub always_show: bool,
+}
+
+impl<T> Entry<T> {
+    /// Creates a new entry with the given string and associated data.
+    pub fn new(
+        string: impl Into<std::borrow::Cow<'static, str>>,
+        data: T,
+    ) -> Self {
+        Self {
+            string: string.into(),
+            data,
+            current: false,
+            tooltip: None,
+            display: None,
+            group: None,
+            keybinding: None,
+            source: None,
+            always_show: false,
+        }
+    }
+
+    /// Marks this entry as the currently active one.
+    pub fn with_current(mut self, current: bool) -> Self {
+        self.current = current;
+        self
+    }
+
+    /// Sets the text to display in place of the entry's string.
+    pub fn with_display(mut self, display: impl Into<String>) -> Self {
+        self.display = Some(display.into());
+        self
+    }
+
+    /// Sets the group this entry is listed under.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Sets the tooltip to show while this entry is selected.
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets the key binding hint displayed next to this entry.
+    pub fn with_keybinding(mut self, keybinding: impl Into<String>) -> Self {
+        self.keybinding = Some(keybinding.into());
+        self
+    }
+
+    /// Sets the source this entry came from.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Lists this entry regardless of the query.
+    pub fn with_always_show(mut self, always_show: bool) -> Self {
+        self.always_show = always_show;
+        self
+    }
 }
 
 impl<T> AsRef<str> for Entry<T> {
@@ -95,486 +278,8410 @@ impl<T> AsRef<str> for Entry<T> {
 #[derive(Debug)]
 pub enum Response {
     /// The user selected a specific entry.
-    Select(usize),
+    Select {
+        /// Index of the selected entry in [`Picker::entries`].
+        index: usize,
+        /// Position of the selected entry in the current search results.
+        rank: usize,
+    },
+    /// The user selected entries while in multi-select mode (see
+    /// [`Picker::enable_multi_select`]). Contains the indices in
+    /// [`Picker::entries`] of the marked entries, in the order they were
+    /// marked, or of just the highlighted entry if no entries were marked.
+    SelectMulti(Vec<usize>),
     /// The user closed the picker without selecting an entry.
     Cancel,
+    /// The user pressed Enter while no entries matched the query, and the
+    /// picker was configured with [`EmptySelect::Query`]. Contains the
+    /// current query.
+    Query(String),
+    /// The user pressed a key registered with
+    /// [`Picker::register_action`].
+    Action {
+        /// The name the key was registered with.
+        name: String,
+        /// Index of the highlighted entry in [`Picker::entries`], if any
+        /// entries currently match the query.
+        entry: Option<usize>,
+    },
+    /// The user didn't grant the plugin the permissions the picker needs
+    /// (see [`Picker::require_permissions`]). The picker shows a notice
+    /// explaining why it is empty, but plugins may want to close it
+    /// instead.
+    PermissionDenied,
+    /// The user asked to reload the entries ([`Action::Refresh`], Ctrl-r
+    /// by default). The picker shows that it is loading until new entries
+    /// are added (usually with [`Picker::clear`] followed by
+    /// [`Picker::extend`]), and keeps the query and the selected entry.
+    Refresh,
+    /// The user answered a prompt started with [`Picker::prompt_number`].
+    Number {
+        /// Index of the entry the prompt was for, in [`Picker::entries`].
+        index: usize,
+        /// The number the user entered.
+        value: i64,
+    },
 }
 
+/// What to do when Enter is pressed while no entries match the query.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-enum InputMode {
+pub enum EmptySelect {
+    /// Do nothing. This is the default.
     #[default]
-    Normal,
-    Search,
+    Ignore,
+    /// Return [`Response::Cancel`].
+    Cancel,
+    /// Return [`Response::Query`] with the current query, for instance to
+    /// allow creating a new item with that name.
+    Query,
 }
 
-/// State of the picker itself.
-#[derive(Default)]
-pub struct Picker<T> {
-    query: String,
-    all_entries: Vec<Entry<T>>,
-    search_results: Vec<SearchResult>,
-    selected: usize,
-    input_mode: InputMode,
-    needs_redraw: bool,
+/// How entries marked as [`current`](Entry::current) are presented.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CurrentEntry {
+    /// Sort the current entry like any other entry. This is the default.
+    #[default]
+    Show,
+    /// Always sort the current entry after all other matching entries.
+    Last,
+    /// Leave the current entry out of the list entirely.
+    Hide,
+}
 
-    pattern: nucleo_matcher::pattern::Pattern,
-    matcher: nucleo_matcher::Matcher,
-    case_matching: nucleo_matcher::pattern::CaseMatching,
+/// Statistics about the work done by the picker, as returned by
+/// [`Picker::metrics`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Metrics {
+    /// How long the most recent search took.
+    pub last_search_duration: std::time::Duration,
+    /// How many entries were checked against the query in the most recent
+    /// search.
+    pub entries_scanned: usize,
+    /// How many entries matched the query in the most recent search.
+    pub matches_found: usize,
+    /// How many times the picker has been rendered since
+    /// [`load`](Picker::load) was called.
+    pub renders: usize,
+    /// How many searches have been completed.
+    pub searches: usize,
 }
 
-impl<T> Picker<T> {
-    /// This function must be called during your plugin's
-    /// [`load`](zellij_tile::ZellijPlugin::load) function.
-    pub fn load(
-        &mut self,
-        configuration: &std::collections::BTreeMap<String, String>,
-    ) {
-        subscribe(PICKER_EVENTS);
+/// Information about how the user arrived at a [`Response`], as returned by
+/// [`Picker::telemetry`]. This is only available with the `telemetry`
+/// feature.
+#[cfg(feature = "telemetry")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Telemetry {
+    /// How long it took from when the picker was loaded (or from the
+    /// previous response) until the response.
+    pub elapsed: std::time::Duration,
+    /// How many keys were pressed in that time.
+    pub keystrokes: usize,
+    /// The query at the time of the response.
+    pub query: String,
+}
 
-        match configuration
-            .get("nucleo_case_matching")
-            .map(|s| s.as_ref())
-        {
-            Some("respect") => {
-                self.case_matching =
-                    nucleo_matcher::pattern::CaseMatching::Respect
-            }
-            Some("ignore") => {
-                self.case_matching =
-                    nucleo_matcher::pattern::CaseMatching::Ignore
-            }
-            Some("smart") => {
-                self.case_matching =
-                    nucleo_matcher::pattern::CaseMatching::Smart
-            }
-            Some(s) => {
-                panic!("unrecognized value {s} for option 'nucleo_case_matching': expected 'respect', 'ignore', 'smart'");
-            }
-            None => {}
-        }
+/// How control characters (tabs, newlines, etc) in entries are rendered.
+/// Matching always uses the original text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ControlChars {
+    /// Replace control characters with a visible symbol (such as `␉` for
+    /// a tab). This is the default.
+    #[default]
+    Replace,
+    /// Leave control characters out of the rendered text.
+    Strip,
+}
 
-        match configuration.get("nucleo_match_paths").map(|s| s.as_ref()) {
-            Some("true") => {
-                self.set_match_paths();
-            }
-            Some("false") => {
-                self.clear_match_paths();
-            }
-            Some(s) => {
-                panic!("unrecognized value {s} for option 'nucleo_match_paths': expected 'true', 'false'");
-            }
-            None => {}
-        }
+/// A read-only summary of the current state of the picker, as passed to the
+/// function registered with [`Picker::set_prompt`].
+#[derive(Debug, Clone, Copy)]
+pub struct PickerView<'a> {
+    /// The current search query.
+    pub query: &'a str,
+    /// Whether the picker is in search mode.
+    pub searching: bool,
+    /// The name of the active custom input mode, if any.
+    pub mode: Option<&'a str>,
+    /// The number of entries matching the current query.
+    pub match_count: usize,
+    /// The total number of entries in the picker.
+    pub entry_count: usize,
+    /// The position of the selected entry in the search results.
+    pub selected: usize,
+    /// The number of marked entries in multi-select mode.
+    pub marked_count: usize,
+}
 
-        match configuration
-            .get("nucleo_start_in_search_mode")
-            .map(|s| s.as_ref())
-        {
-            Some("true") => {
-                self.enter_search_mode();
-            }
-            Some("false") => {
-                self.enter_normal_mode();
-            }
-            Some(s) => {
-                panic!("unrecognized value {s} for option 'nucleo_start_in_search_mode': expected 'true', 'false'");
-            }
-            None => {}
-        }
-    }
+/// A single search result, as returned by [`Picker::results`].
+#[derive(Debug)]
+pub struct SearchResultView<'a, T> {
+    /// Index of the entry in [`Picker::entries`].
+    pub index: usize,
+    /// The matching entry.
+    pub entry: &'a Entry<T>,
+    /// The score the entry was given when matching it against the query.
+    /// This is 0 for every entry when the query is empty.
+    pub score: u32,
+    /// Positions (in graphemes, not characters) of the parts of the entry's
+    /// string which matched the query.
+    pub indices: &'a [u32],
+}
 
-    /// This function must be called during your plugin's
-    /// [`update`](zellij_tile::ZellijPlugin::update) function. If an entry
-    /// was selected or the picker was closed, this function will return a
-    /// [`Response`]. This function will update the picker's internal state
-    /// of whether it needs to redraw the picker, so your plugin's
-    /// [`update`](zellij_tile::ZellijPlugin::update) function should return
-    /// true if [`needs_redraw`](Self::needs_redraw) returns true.
-    pub fn update(&mut self, event: &Event) -> Option<Response> {
-        match event {
-            Event::Key(key) => self.handle_key(key),
-            _ => None,
-        }
-    }
+/// Where the preview area (see [`Picker::set_preview`]) is placed.
+#[cfg(feature = "preview")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewPosition {
+    /// Show the preview in the right half of the pane. This is the
+    /// default.
+    #[default]
+    Right,
+    /// Show the preview in the bottom half of the pane.
+    Bottom,
+}
 
-    /// This function must be called during your plugin's
-    /// [`render`](zellij_tile::ZellijPlugin::render) function.
-    pub fn render(&mut self, rows: usize, cols: usize) {
-        if rows == 0 {
-            return;
-        }
+/// Where the prompt line is placed (see [`Picker::set_prompt_position`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PromptPosition {
+    /// Show the prompt at the top of the pane. This is the default.
+    #[default]
+    Top,
+    /// Show the prompt at the bottom of the pane, which works well for
+    /// pickers in a pane at the bottom of the screen.
+    Bottom,
+}
+
+/// Which way the list of entries grows (see
+/// [`Picker::set_list_direction`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ListDirection {
+    /// The best match is at the top of the list. This is the default.
+    #[default]
+    Down,
+    /// The best match is at the bottom of the list, like fzf's default
+    /// layout. This is usually combined with [`PromptPosition::Bottom`],
+    /// so that the best match is next to the prompt.
+    Up,
+}
 
-        let visible_entry_count = rows - 1;
-        let visible_entries_start =
-            (self.selected / visible_entry_count) * visible_entry_count;
-        let visible_selected = self.selected % visible_entry_count;
+/// How terms in the query which don't use any special syntax are matched
+/// (see [`Picker::set_match_mode`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The characters of the term must appear in order, but not
+    /// necessarily next to each other. This is the default.
+    #[default]
+    Fuzzy,
+    /// The term must appear somewhere in the entry.
+    Substring,
+    /// The entry must start with the term.
+    Prefix,
+    /// The entry must end with the term.
+    Suffix,
+    /// The entry must be exactly the term.
+    Exact,
+}
 
-        print!("  ");
-        if self.input_mode == InputMode::Normal && self.query.is_empty() {
-            print!(
-                "{}",
-                "(press / to search)".fg::<owo_colors::colors::BrightBlack>()
-            );
-        } else {
-            print!("{}", self.query);
-            if self.input_mode == InputMode::Search {
-                print!("{}", " ".bg::<owo_colors::colors::Green>());
+impl MatchMode {
+    // terms without any special syntax are parsed as fuzzy atoms, so those
+    // are the ones which get switched over to the configured kind
+    fn apply(self, pattern: &mut nucleo_matcher::pattern::Pattern) {
+        use nucleo_matcher::pattern::AtomKind;
+        let kind = match self {
+            Self::Fuzzy => return,
+            Self::Substring => AtomKind::Substring,
+            Self::Prefix => AtomKind::Prefix,
+            Self::Suffix => AtomKind::Postfix,
+            Self::Exact => AtomKind::Exact,
+        };
+        for atom in &mut pattern.atoms {
+            if atom.kind == AtomKind::Fuzzy {
+                atom.kind = kind;
             }
         }
-        println!();
+    }
+}
 
-        let lines: Vec<_> = self
-            .search_results
-            .iter()
-            .skip(visible_entries_start)
-            .take(visible_entry_count)
-            .enumerate()
-            .map(|(i, search_result)| {
-                let mut line = String::new();
+/// The direction of the column search results are sorted by (see
+/// [`Picker::add_sort_column`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Sort from the smallest value to the largest.
+    Ascending,
+    /// Sort from the largest value to the smallest.
+    Descending,
+}
 
-                if i == visible_selected {
-                    write!(
-                        &mut line,
-                        "{} ",
-                        ">".fg::<owo_colors::colors::Yellow>()
-                    )
-                    .unwrap();
-                } else {
-                    write!(&mut line, "  ").unwrap();
-                }
+/// How to order search results which were given the same score.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tiebreak {
+    /// Prefer entries where the match starts earlier, and then sort
+    /// alphabetically. This is the default.
+    #[default]
+    Alphabetical,
+    /// Like [`Alphabetical`](Self::Alphabetical), but runs of digits are
+    /// compared numerically, so that `tab 2` sorts before `tab 10`.
+    Natural,
+    /// Keep entries in the order they were added to the picker. This is
+    /// useful when the order of the entries is meaningful, such as for
+    /// lists of recently used items.
+    SourceOrder,
+}
 
-                let mut current_col = 2;
-                for (char_idx, c) in self.all_entries[search_result.entry]
-                    .string
-                    .chars()
-                    .enumerate()
-                {
-                    let width = c.width().unwrap_or(0);
-                    if current_col + width > cols - 6 {
-                        write!(
-                            &mut line,
-                            "{}",
-                            " [...]".fg::<owo_colors::colors::BrightBlack>()
-                        )
-                        .unwrap();
-                        break;
-                    }
+/// The rules used to order search results with the same score, as a
+/// versioned contract (see [`Picker::set_ranking_profile`]). Improved
+/// rules are added as new profiles rather than by changing existing ones,
+/// so the order produced by a profile stays the same across versions of
+/// this crate, and plugins can opt into newer profiles deliberately.
+///
+/// In every profile, the options which reorder results regardless of
+/// their scores (such as groups, pinned entries and
+/// [`set_prefix_first`](Picker::set_prefix_first)) are applied first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RankingProfile {
+    /// Results are ordered by score, then by where the match starts
+    /// (earlier first), and then according to the [`Tiebreak`] (which
+    /// replaces the match position for [`Tiebreak::SourceOrder`]). This
+    /// is the default.
+    #[default]
+    V1,
+    /// Like [`V1`](Self::V1), but results with the same score and match
+    /// position list shorter entries first, before applying the
+    /// [`Tiebreak`], so that `foo` is listed before `foobar` when searching
+    /// for `foo`. [`Tiebreak::SourceOrder`] still keeps the order the
+    /// entries were added in.
+    V2,
+}
 
-                    if search_result
-                        .indices
-                        .contains(&u32::try_from(char_idx).unwrap())
-                    {
-                        write!(
-                            &mut line,
-                            "{}",
-                            c.fg::<owo_colors::colors::Cyan>()
-                        )
-                        .unwrap();
-                    } else if i == visible_selected {
-                        write!(
-                            &mut line,
-                            "{}",
-                            c.fg::<owo_colors::colors::Yellow>()
-                        )
-                        .unwrap();
-                    } else {
-                        write!(&mut line, "{}", c).unwrap();
-                    }
+/// A color used by a [`Theme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// A color from the 256 color palette.
+    Fixed(u8),
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
 
-                    current_col += width;
-                }
-                line
-            })
-            .collect();
+impl Color {
+    fn dyn_color(self) -> owo_colors::DynColors {
+        use owo_colors::AnsiColors;
+        let ansi = match self {
+            Self::Black => AnsiColors::Black,
+            Self::Red => AnsiColors::Red,
+            Self::Green => AnsiColors::Green,
+            Self::Yellow => AnsiColors::Yellow,
+            Self::Blue => AnsiColors::Blue,
+            Self::Magenta => AnsiColors::Magenta,
+            Self::Cyan => AnsiColors::Cyan,
+            Self::White => AnsiColors::White,
+            Self::BrightBlack => AnsiColors::BrightBlack,
+            Self::BrightRed => AnsiColors::BrightRed,
+            Self::BrightGreen => AnsiColors::BrightGreen,
+            Self::BrightYellow => AnsiColors::BrightYellow,
+            Self::BrightBlue => AnsiColors::BrightBlue,
+            Self::BrightMagenta => AnsiColors::BrightMagenta,
+            Self::BrightCyan => AnsiColors::BrightCyan,
+            Self::BrightWhite => AnsiColors::BrightWhite,
+            Self::Fixed(n) => return owo_colors::DynColors::Xterm(n.into()),
+            Self::Rgb(r, g, b) => return owo_colors::DynColors::Rgb(r, g, b),
+        };
+        owo_colors::DynColors::Ansi(ansi)
+    }
+}
 
-        print!("{}", lines.join("\n"));
+impl From<PaletteColor> for Color {
+    fn from(color: PaletteColor) -> Self {
+        match color {
+            PaletteColor::Rgb((r, g, b)) => Self::Rgb(r, g, b),
+            PaletteColor::EightBit(n) => Self::Fixed(n),
+        }
+    }
+}
 
-        self.needs_redraw = false;
+/// The colors and attributes of a single element of the picker. Colors
+/// which are `None` use the terminal's default.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextStyle {
+    /// The foreground color.
+    pub fg: Option<Color>,
+    /// The background color.
+    pub bg: Option<Color>,
+    /// Whether the text is bold.
+    pub bold: bool,
+    /// Whether the text is underlined.
+    pub underline: bool,
+    /// Whether the foreground and background colors are swapped.
+    pub reversed: bool,
+}
+
+impl TextStyle {
+    /// Creates a style with only a foreground color.
+    pub fn fg(color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::default()
+        }
     }
 
-    /// Returns true if the picker needs to be redrawn. Your plugin's
-    /// [`update`](zellij_tile::ZellijPlugin::update) function should return
-    /// true if this function returns true.
-    pub fn needs_redraw(&self) -> bool {
-        self.needs_redraw
+    /// Creates a style with only a background color.
+    pub fn bg(color: Color) -> Self {
+        Self {
+            bg: Some(color),
+            ..Self::default()
+        }
     }
 
-    /// Returns the current list of entries in the picker.
-    pub fn entries(&self) -> &[Entry<T>] {
-        &self.all_entries
+    /// Makes the text bold.
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
     }
 
-    /// Forces a specific entry in the list of entries to be selected.
-    pub fn select(&mut self, idx: usize) {
-        self.selected = idx;
-        self.needs_redraw = true;
+    /// Underlines the text.
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
     }
 
-    /// Removes all entries in the list.
-    pub fn clear(&mut self) {
-        self.all_entries.clear();
-        self.search();
+    /// Swaps the foreground and background colors.
+    pub fn reversed(mut self) -> Self {
+        self.reversed = true;
+        self
     }
 
-    /// Adds new entries to the list.
-    pub fn extend(&mut self, iter: impl IntoIterator<Item = Entry<T>>) {
-        let prev_selected =
-            self.search_results.get(self.selected).map(|search_result| {
-                self.all_entries[search_result.entry].string.clone()
-            });
+    fn paint<D: std::fmt::Display>(self, text: D) -> owo_colors::Styled<D> {
+        let mut style = owo_colors::Style::new();
+        if let Some(fg) = self.fg {
+            style = style.color(fg.dyn_color());
+        }
+        if let Some(bg) = self.bg {
+            style = style.on_color(bg.dyn_color());
+        }
+        if self.bold {
+            style = style.bold();
+        }
+        if self.underline {
+            style = style.underline();
+        }
+        if self.reversed {
+            style = style.reversed();
+        }
+        style.style(text)
+    }
+}
 
-        self.all_entries.extend(iter);
-        self.search();
+/// The colors used to draw the picker (see [`Picker::set_theme`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The selected entry and its indicator.
+    pub selected: TextStyle,
+    /// The characters of an entry which matched the query.
+    pub matched: TextStyle,
+    /// The cursor in the query.
+    pub cursor: TextStyle,
+    /// Placeholder text, separators, truncation markers and other hints.
+    pub hint: TextStyle,
+    /// The indicator for marked entries in multi-select mode.
+    pub marked: TextStyle,
+    /// The indicator for entries marked as current.
+    pub current: TextStyle,
+    /// The macro recording indicator.
+    pub recording: TextStyle,
+    /// Section headings in the help screen.
+    pub heading: TextStyle,
+    /// Key names in the help screen.
+    pub key: TextStyle,
+    /// Entries which were added recently (see
+    /// [`Picker::set_highlight_new`]).
+    pub new_entry: TextStyle,
+    /// Every other entry in the list, to make long lists easier to scan.
+    /// This usually only sets a background color. Striping is disabled
+    /// when this is left as the default style.
+    pub stripe: TextStyle,
+}
 
-        if let Some(prev_selected) = prev_selected {
-            self.selected = self
-                .search_results
-                .iter()
-                .enumerate()
-                .find_map(|(idx, search_result)| {
-                    (self.all_entries[search_result.entry].string
-                        == prev_selected)
-                        .then_some(idx)
-                })
-                .unwrap_or(0);
-        } else {
-            self.selected = 0;
+impl Theme {
+    /// Creates a theme using the colors of a zellij palette, as found in
+    /// [`ModeInfo`].
+    pub fn from_palette(palette: &Palette) -> Self {
+        Self {
+            selected: TextStyle::fg(palette.yellow.into()),
+            matched: TextStyle::fg(palette.cyan.into()),
+            cursor: TextStyle::bg(palette.green.into()),
+            hint: TextStyle::fg(palette.gray.into()),
+            marked: TextStyle::fg(palette.magenta.into()),
+            current: TextStyle::fg(palette.green.into()),
+            recording: TextStyle::fg(palette.red.into()),
+            heading: TextStyle::fg(palette.yellow.into()),
+            key: TextStyle::fg(palette.cyan.into()),
+            new_entry: TextStyle::fg(palette.green.into()),
+            stripe: TextStyle::default(),
         }
     }
 
-    /// Request that the fuzzy matcher always respect case when matching.
-    pub fn use_case_matching_respect(&mut self) {
-        self.case_matching = nucleo_matcher::pattern::CaseMatching::Respect;
+    /// A theme using bright colors and bold text, for readability on
+    /// terminals or displays with poor contrast.
+    pub fn high_contrast() -> Self {
+        Self {
+            selected: TextStyle::fg(Color::BrightYellow).bold(),
+            matched: TextStyle::fg(Color::BrightCyan).bold().underline(),
+            cursor: TextStyle::default().reversed(),
+            hint: TextStyle::fg(Color::White),
+            marked: TextStyle::fg(Color::BrightMagenta).bold(),
+            current: TextStyle::fg(Color::BrightGreen).bold(),
+            recording: TextStyle::fg(Color::BrightRed).bold(),
+            heading: TextStyle::fg(Color::BrightYellow).bold(),
+            key: TextStyle::fg(Color::BrightCyan).bold(),
+            new_entry: TextStyle::fg(Color::BrightGreen),
+            stripe: TextStyle::default(),
+        }
     }
 
-    /// Request that the fuzzy matcher always ignore case when matching.
-    pub fn use_case_matching_ignore(&mut self) {
-        self.case_matching = nucleo_matcher::pattern::CaseMatching::Ignore;
+    /// A theme which doesn't rely on distinguishing red from green, using
+    /// the colorblind-safe palette from Okabe and Ito.
+    pub fn deuteranopia() -> Self {
+        let orange = Color::Rgb(230, 159, 0);
+        let sky_blue = Color::Rgb(86, 180, 233);
+        let yellow = Color::Rgb(240, 228, 66);
+        let blue = Color::Rgb(0, 114, 178);
+        let vermillion = Color::Rgb(213, 94, 0);
+        let reddish_purple = Color::Rgb(204, 121, 167);
+        Self {
+            selected: TextStyle::fg(orange).bold(),
+            matched: TextStyle::fg(sky_blue),
+            cursor: TextStyle::bg(blue),
+            hint: TextStyle::fg(Color::BrightBlack),
+            marked: TextStyle::fg(reddish_purple),
+            current: TextStyle::fg(blue),
+            recording: TextStyle::fg(vermillion),
+            heading: TextStyle::fg(orange),
+            key: TextStyle::fg(sky_blue),
+            new_entry: TextStyle::fg(yellow),
+            stripe: TextStyle::default(),
+        }
     }
 
-    /// Request that the fuzzy matcher respect case when matching if the
-    /// query contains any uppercase characters, but ignore case otherwise.
-    /// This is the default.
-    pub fn use_case_matching_smart(&mut self) {
-        self.case_matching = nucleo_matcher::pattern::CaseMatching::Smart;
+    /// A theme which doesn't use any colors, only text attributes.
+    pub fn monochrome() -> Self {
+        Self {
+            selected: TextStyle::default().bold(),
+            matched: TextStyle::default().underline(),
+            cursor: TextStyle::default().reversed(),
+            hint: TextStyle::default(),
+            marked: TextStyle::default().bold(),
+            current: TextStyle::default().bold(),
+            recording: TextStyle::default().reversed(),
+            heading: TextStyle::default().bold().underline(),
+            key: TextStyle::default().bold(),
+            new_entry: TextStyle::default().bold(),
+            stripe: TextStyle::default(),
+        }
     }
+}
 
-    /// Puts the picker into search mode (equivalent to pressing `/` when in
-    /// normal mode).
-    pub fn enter_search_mode(&mut self) {
-        self.input_mode = InputMode::Search;
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            selected: TextStyle::fg(Color::Yellow),
+            matched: TextStyle::fg(Color::Cyan),
+            cursor: TextStyle::bg(Color::Green),
+            hint: TextStyle::fg(Color::BrightBlack),
+            marked: TextStyle::fg(Color::Magenta),
+            current: TextStyle::fg(Color::Green),
+            recording: TextStyle::fg(Color::Red),
+            heading: TextStyle::fg(Color::Yellow),
+            key: TextStyle::fg(Color::Cyan),
+            new_entry: TextStyle::fg(Color::BrightGreen),
+            stripe: TextStyle::default(),
+        }
     }
+}
 
-    /// Puts the picker into normal mode (equivalent to pressing Escape when
-    /// in search mode). This is the default.
-    pub fn enter_normal_mode(&mut self) {
-        self.input_mode = InputMode::Normal;
-    }
+/// A set of key bindings modeled after a familiar program (see
+/// [`Picker::set_keymap`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Keymap {
+    /// Only the built in keys. This is the default.
+    #[default]
+    Default,
+    /// Keys from fzf: C-n/C-j and C-p/C-k to move, Esc and C-g to cancel.
+    Fzf,
+    /// Keys from vim: C-n/C-p to move, C-f/C-b to page, g/G to jump to
+    /// the ends of the list, and i to start searching.
+    Vim,
+    /// Keys from emacs: C-n/C-p to move, C-v/M-v to page, M-</M-> to jump
+    /// to the ends of the list, and C-g to cancel.
+    Emacs,
+}
 
-    /// Configures the fuzzy matcher to adjust matching bonuses appropriate
-    /// for matching paths.
-    pub fn set_match_paths(&mut self) {
-        self.matcher.config.set_match_paths();
+impl Keymap {
+    fn bindings(self) -> Vec<(KeyWithModifier, Action)> {
+        let key = |c| KeyWithModifier::new(BareKey::Char(c));
+        let ctrl = |c| key(c).with_ctrl_modifier();
+        let alt = |c| key(c).with_alt_modifier();
+        match self {
+            Self::Default => vec![],
+            Self::Fzf => vec![
+                (ctrl('n'), Action::Down),
+                (ctrl('j'), Action::Down),
+                (ctrl('p'), Action::Up),
+                (ctrl('k'), Action::Up),
+                (ctrl('g'), Action::Cancel),
+                (KeyWithModifier::new(BareKey::Esc), Action::Cancel),
+            ],
+            Self::Vim => vec![
+                (ctrl('n'), Action::Down),
+                (ctrl('p'), Action::Up),
+                (ctrl('f'), Action::PageDown),
+                (ctrl('b'), Action::PageUp),
+                (key('g'), Action::First),
+                (key('G'), Action::Last),
+                (key('i'), Action::EnterSearchMode),
+            ],
+            Self::Emacs => vec![
+                (ctrl('n'), Action::Down),
+                (ctrl('p'), Action::Up),
+                (ctrl('v'), Action::PageDown),
+                (alt('v'), Action::PageUp),
+                (alt('<'), Action::First),
+                (alt('>'), Action::Last),
+                (ctrl('g'), Action::Cancel),
+            ],
+        }
     }
+}
 
-    /// Configures the fuzzy matcher to adjust matching bonuses appropriate
-    /// for matching arbitrary strings. This is the default.
-    pub fn clear_match_paths(&mut self) {
-        self.matcher.config = nucleo_matcher::Config::DEFAULT;
+// the keys which are bound without any keymap, along with the input mode
+// they are bound in (or None for all of the built in modes)
+fn builtin_bindings() -> Vec<(KeyWithModifier, Option<InputMode>, Action)> {
+    let key = KeyWithModifier::new;
+    let ctrl = |c| key(BareKey::Char(c)).with_ctrl_modifier();
+    let alt = |c| key(BareKey::Char(c)).with_alt_modifier();
+    let normal = Some(InputMode::Normal);
+    let search = Some(InputMode::Search);
+    vec![
+        (key(BareKey::Up), None, Action::Up),
+        (key(BareKey::Tab).with_shift_modifier(), None, Action::Up),
+        (key(BareKey::Char('k')), normal, Action::Up),
+        (key(BareKey::Down), None, Action::Down),
+        (key(BareKey::Tab), None, Action::Down),
+        (key(BareKey::Char('j')), normal, Action::Down),
+        (key(BareKey::PageUp), None, Action::PageUp),
+        (key(BareKey::PageDown), None, Action::PageDown),
+        (key(BareKey::Home), normal, Action::First),
+        (key(BareKey::End), normal, Action::Last),
+        (key(BareKey::Enter), None, Action::Select),
+        (ctrl('c'), None, Action::Cancel),
+        (ctrl('u'), search, Action::ClearQuery),
+        (key(BareKey::Backspace), search, Action::DeleteChar),
+        (key(BareKey::Char('/')), normal, Action::EnterSearchMode),
+        (key(BareKey::Esc), None, Action::ExitSearchMode),
+        (key(BareKey::F(1)), None, Action::ShowHelp),
+        (key(BareKey::Char('?')), normal, Action::ShowHelp),
+        (ctrl('r'), None, Action::Refresh),
+        (ctrl('o'), None, Action::JumpBack),
+        // most terminals send the same thing for C-i and Tab, so M-i is
+        // also bound
+        (alt('i'), None, Action::JumpForward),
+        (ctrl('i'), None, Action::JumpForward),
+        (alt('c'), None, Action::TogglePreferExactCase),
+    ]
+}
+
+// formats a key the way the help screen lists them, such as `C-c` or `PgDn`
+fn key_label(key: &KeyWithModifier) -> String {
+    let mut label = String::new();
+    for (modifier, prefix) in [
+        (KeyModifier::Ctrl, "C-"),
+        (KeyModifier::Alt, "M-"),
+        (KeyModifier::Shift, "S-"),
+        (KeyModifier::Super, "s-"),
+    ] {
+        if key.key_modifiers.contains(&modifier) {
+            label.push_str(prefix);
+        }
+    }
+    match key.bare_key {
+        BareKey::Char(' ') => label.push_str("Space"),
+        BareKey::Char(c) => label.push(c),
+        BareKey::Left => label.push_str("Left"),
+        BareKey::Right => label.push_str("Right"),
+        BareKey::Up => label.push_str("Up"),
+        BareKey::Down => label.push_str("Down"),
+        BareKey::Home => label.push_str("Home"),
+        BareKey::End => label.push_str("End"),
+        BareKey::Backspace => label.push_str("Backspace"),
+        BareKey::Delete => label.push_str("Delete"),
+        BareKey::Insert => label.push_str("Insert"),
+        BareKey::Tab => label.push_str("Tab"),
+        BareKey::Esc => label.push_str("Esc"),
+        BareKey::Enter => label.push_str("Enter"),
+        ref bare_key => write!(label, "{bare_key}").unwrap(),
     }
+    label
+}
 
-    fn search(&mut self) {
-        self.pattern.reparse(
-            &self.query,
-            self.case_matching,
-            nucleo_matcher::pattern::Normalization::Smart,
-        );
-        let mut haystack = vec![];
-        self.search_results = self
-            .all_entries
-            .iter()
-            .enumerate()
-            .filter_map(|(i, entry)| {
-                let haystack = nucleo_matcher::Utf32Str::new(
-                    &entry.string,
-                    &mut haystack,
-                );
-                let mut indices = vec![];
-                self.pattern
-                    .indices(haystack, &mut self.matcher, &mut indices)
-                    .map(|score| SearchResult {
-                        entry: i,
-                        score,
-                        indices,
-                    })
-            })
-            .collect();
-        self.search_results.sort_by_key(|search_result| {
-            SearchResultWithString {
-                score: search_result.score,
-                first_index: search_result.indices.first().copied(),
-                string: &self.all_entries[search_result.entry].string,
-            }
-        });
+/// How many clicks it takes to choose an entry with the mouse (see
+/// [`Picker::enable_mouse`]).
+#[cfg(feature = "mouse")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MouseSelect {
+    /// A single click selects an entry, and double-clicking chooses it.
+    /// This is the default.
+    #[default]
+    DoubleClick,
+    /// A single click chooses an entry.
+    SingleClick,
+}
 
-        self.needs_redraw = true;
+/// Whether pressing a digit in normal mode chooses the entry at that
+/// position (see [`Picker::set_digit_shortcuts`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DigitShortcuts {
+    /// Digits choose entries by position. This is the default.
+    #[default]
+    Enabled,
+    /// Digits choose entries by position unless some entry starts with a
+    /// digit, in which case they start a search like
+    /// [`Disabled`](Self::Disabled).
+    Auto,
+    /// Digits enter search mode and start a search for the typed digit.
+    Disabled,
+}
+
+/// Which parts of an entry are highlighted as matching the query (see
+/// [`Picker::set_match_highlight`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MatchHighlight {
+    /// Only the characters which matched the query are highlighted. This
+    /// is the default.
+    #[default]
+    Characters,
+    /// Each word containing a character which matched the query is
+    /// highlighted in its entirety.
+    Words,
+}
+
+/// Score thresholds for separating the results into strong, medium and
+/// weak matches (see [`Picker::set_score_bands`]). Thresholds are
+/// percentages of the score of the best matching entry, since the scale of
+/// the scores depends on the length of the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreBands {
+    /// Results scoring at least this percentage of the best score are
+    /// strong matches. Defaults to 90.
+    pub strong: u32,
+    /// Results scoring at least this percentage of the best score (but
+    /// less than [`strong`](Self::strong)) are medium matches, and the
+    /// rest are weak matches. Defaults to 75.
+    pub medium: u32,
+}
+
+impl Default for ScoreBands {
+    fn default() -> Self {
+        Self {
+            strong: 90,
+            medium: 75,
+        }
     }
+}
 
-    fn handle_key(&mut self, key: &KeyWithModifier) -> Option<Response> {
-        self.handle_global_key(key)
-            .or_else(|| match self.input_mode {
-                InputMode::Normal => self.handle_normal_key(key),
-                InputMode::Search => self.handle_search_key(key),
-            })
+impl ScoreBands {
+    fn band(&self, score: u32, best: u32) -> &'static str {
+        let percent = u64::from(score) * 100 / u64::from(best.max(1));
+        if percent >= u64::from(self.strong) {
+            "strong"
+        } else if percent >= u64::from(self.medium) {
+            "medium"
+        } else {
+            "weak"
+        }
     }
+}
 
-    fn handle_normal_key(
-        &mut self,
-        key: &KeyWithModifier,
-    ) -> Option<Response> {
-        match key.bare_key {
-            BareKey::Char('j') if key.has_no_modifiers() => {
-                self.down();
+/// How to indicate the scroll position of the list of entries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollIndicator {
+    /// Don't indicate the scroll position. This is the default.
+    #[default]
+    Hidden,
+    /// Draw a scrollbar along the right edge of the list when it doesn't
+    /// fit on the screen.
+    Scrollbar,
+    /// Show the position of the selected entry ("3 of 120") in the prompt
+    /// line.
+    Position,
+}
+
+/// An action that can be bound to a key with [`Picker::bind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Select the previous entry.
+    Up,
+    /// Select the next entry.
+    Down,
+    /// Move the selection up by a screenful of entries.
+    PageUp,
+    /// Move the selection down by a screenful of entries.
+    PageDown,
+    /// Select the first entry.
+    First,
+    /// Select the last entry.
+    Last,
+    /// Choose the selected entry (or the marked entries, in multi-select
+    /// mode).
+    Select,
+    /// Close the picker without choosing anything.
+    Cancel,
+    /// Clear the query.
+    ClearQuery,
+    /// Delete the character before the cursor in the query.
+    DeleteChar,
+    /// Switch between normal mode and search mode.
+    ToggleSearchMode,
+    /// Enter search mode.
+    EnterSearchMode,
+    /// Return to normal mode.
+    ExitSearchMode,
+    /// Mark or unmark the selected entry in multi-select mode.
+    ToggleMark,
+    /// Show the help screen.
+    ShowHelp,
+    /// Ask the plugin to reload the entries (see [`Response::Refresh`]).
+    Refresh,
+    /// Select the entry which was selected before the last jump. Moving
+    /// the selection by more than one entry at a time (such as with Home,
+    /// End or Page Down, or by changing the query) counts as a jump.
+    JumpBack,
+    /// Undo [`JumpBack`](Self::JumpBack). This is bound to both `Ctrl i`
+    /// and `Alt i`, since most terminals can't tell `Ctrl i` apart from
+    /// Tab.
+    JumpForward,
+    /// Toggle listing matches with the same case as the query first (see
+    /// [`Picker::set_prefer_exact_case`]).
+    TogglePreferExactCase,
+}
+
+impl Action {
+    const ALL: &'static [Self] = &[
+        Self::Up,
+        Self::Down,
+        Self::PageUp,
+        Self::PageDown,
+        Self::First,
+        Self::Last,
+        Self::Select,
+        Self::Cancel,
+        Self::ClearQuery,
+        Self::DeleteChar,
+        Self::ToggleSearchMode,
+        Self::EnterSearchMode,
+        Self::ExitSearchMode,
+        Self::ToggleMark,
+        Self::ShowHelp,
+        Self::Refresh,
+        Self::JumpBack,
+        Self::JumpForward,
+        Self::TogglePreferExactCase,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::PageUp => "page_up",
+            Self::PageDown => "page_down",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::Select => "select",
+            Self::Cancel => "cancel",
+            Self::ClearQuery => "clear_query",
+            Self::DeleteChar => "delete_char",
+            Self::ToggleSearchMode => "toggle_search_mode",
+            Self::EnterSearchMode => "enter_search_mode",
+            Self::ExitSearchMode => "exit_search_mode",
+            Self::ToggleMark => "toggle_mark",
+            Self::ShowHelp => "show_help",
+            Self::Refresh => "refresh",
+            Self::JumpBack => "jump_back",
+            Self::JumpForward => "jump_forward",
+            Self::TogglePreferExactCase => "toggle_prefer_exact_case",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Up => "select previous entry",
+            Self::Down => "select next entry",
+            Self::PageUp => "move up a page",
+            Self::PageDown => "move down a page",
+            Self::First => "select the first entry",
+            Self::Last => "select the last entry",
+            Self::Select => "choose the selected entry",
+            Self::Cancel => "cancel",
+            Self::ClearQuery => "clear the query",
+            Self::DeleteChar => "delete the character before the cursor",
+            Self::ToggleSearchMode => "toggle search mode",
+            Self::EnterSearchMode => "enter search mode",
+            Self::ExitSearchMode => "leave search mode",
+            Self::ToggleMark => "mark the selected entry",
+            Self::ShowHelp => "show this help",
+            Self::Refresh => "reload the entries",
+            Self::JumpBack => "select the previously selected entry",
+            Self::JumpForward => "undo jumping back",
+            Self::TogglePreferExactCase => {
+                "toggle listing exact case matches first"
             }
-            BareKey::Char('k') if key.has_no_modifiers() => {
-                self.up();
+        }
+    }
+}
+
+/// A message displayed by the picker, along with the values it includes,
+/// for replacing the built-in texts with
+/// [`set_message_formatter`](Picker::set_message_formatter). The default
+/// English texts are available through the [`Display`](std::fmt::Display)
+/// implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    /// The position of the selected entry, shown in the prompt line with
+    /// [`ScrollIndicator::Position`]: `3 of 120`.
+    Position {
+        /// The position of the selected entry, starting from 1.
+        selected: usize,
+        /// The number of search results.
+        total: usize,
+    },
+    /// The number of matching entries, shown in the status line (see
+    /// [`Picker::set_status_line`]): `12/40`.
+    Matches {
+        /// The number of entries matching the query.
+        matched: usize,
+        /// The number of entries which aren't hidden.
+        total: usize,
+    },
+    /// The number of marked entries in multi-select mode, shown in the
+    /// status line: `3 entries marked`.
+    Marked {
+        /// The number of marked entries.
+        count: usize,
+    },
+    /// Shown in the prompt line while a search is in progress: `searching`.
+    Searching,
+    /// Shown in the prompt line while recording a macro: `recording`.
+    Recording,
+    /// Shown in the footer after pressing Escape once with
+    /// [`Picker::set_double_esc_cancel`]: `press Esc again to cancel`.
+    EscAgain,
+    /// Shown at the bottom of the help screen: `press any key to close`.
+    CloseHelp,
+    /// Shown in the footer when the permissions the picker needs were
+    /// denied (see [`Picker::require_permissions`]).
+    PermissionDenied,
+    /// Shown after an invalid answer to [`Picker::prompt_number`]:
+    /// `enter a number from 1 to 100`.
+    InvalidNumber {
+        /// The smallest number accepted.
+        start: i64,
+        /// The largest number accepted.
+        end: i64,
+    },
+}
+
+impl std::fmt::Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Position { selected, total } => {
+                write!(f, "{selected} of {total}")
             }
-            BareKey::Char(c @ '1'..='8') if key.has_no_modifiers() => {
-                let position =
-                    usize::try_from(c.to_digit(10).unwrap() - 1).unwrap();
-                return self.search_results.get(position).map(
-                    |search_result| Response::Select(search_result.entry),
-                );
+            Self::Matches { matched, total } => {
+                write!(f, "{matched}/{total}")
             }
-            BareKey::Char('9') if key.has_no_modifiers() => {
-                return self.search_results.last().map(|search_result| {
-                    Response::Select(search_result.entry)
-                })
+            Self::Marked { count } => {
+                write!(f, "{} marked", plural(count, "entry", "entries"))
             }
-            BareKey::Char('/') if key.has_no_modifiers() => {
-                self.input_mode = InputMode::Search;
-                self.needs_redraw = true;
+            Self::Searching => write!(f, "searching"),
+            Self::Recording => write!(f, "recording"),
+            Self::EscAgain => write!(f, "press Esc again to cancel"),
+            Self::CloseHelp => write!(f, "press any key to close"),
+            Self::PermissionDenied => {
+                write!(
+                    f,
+                    "permission denied: this picker can't load its entries"
+                )
+            }
+            Self::InvalidNumber { start, end } => {
+                write!(f, "enter a number from {start} to {end}")
             }
-            _ => {}
         }
+    }
+}
 
-        None
+/// Formats a count followed by the singular or plural form of a noun, as
+/// appropriate: `plural(1, "entry", "entries")` is `1 entry`, and
+/// `plural(3, "entry", "entries")` is `3 entries`. This is useful for
+/// custom messages (see [`Picker::set_message_formatter`]).
+pub fn plural(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{count} {singular}")
+    } else {
+        format!("{count} {plural}")
     }
+}
 
-    fn handle_search_key(
-        &mut self,
-        key: &KeyWithModifier,
-    ) -> Option<Response> {
-        match key.bare_key {
-            BareKey::Char(c) if key.has_no_modifiers() => {
-                self.query.push(c);
-                self.search();
-                self.selected = 0;
+/// How often and how recently an entry was chosen, used to rank entries by
+/// frecency (see [`Picker::set_frecency_weight`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// The number of times the entry was chosen.
+    pub count: u32,
+    /// When the entry was last chosen, in seconds since the Unix epoch.
+    pub last_used: u64,
+}
+
+impl Usage {
+    fn frecency(&self, now: u64) -> u32 {
+        let age = now.saturating_sub(self.last_used);
+        let recency = if age < 60 * 60 {
+            8
+        } else if age < 24 * 60 * 60 {
+            4
+        } else if age < 7 * 24 * 60 * 60 {
+            2
+        } else {
+            1
+        };
+        self.count.saturating_mul(recency)
+    }
+}
+
+/// Storage for the state which the picker keeps between plugin instances:
+/// the selection history, the usage data for frecency ranking, and the
+/// pinned entries (see [`Picker::set_store`]), as well as the directories
+/// and bookmarks of a [`DirectoryPicker`]. Each of these is saved as a
+/// separate blob of bytes under its own key.
+pub trait Store {
+    /// Returns the blob saved under `key`, or `None` if nothing was saved
+    /// there yet.
+    fn load(&self, key: &str) -> std::io::Result<Option<Vec<u8>>>;
+
+    /// Saves `data` under `key`, replacing anything saved there before.
+    fn save(&mut self, key: &str, data: &[u8]) -> std::io::Result<()>;
+}
+
+/// A [`Store`] which saves each blob to a file in a directory. The default
+/// directory is `/cache`, which zellij maps to a cache directory for your
+/// plugin. Pickers which should have separate state need separate
+/// directories.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: std::path::PathBuf,
+}
+
+impl FileStore {
+    /// Creates a store which saves files in `dir`. The directory is created
+    /// when something is first saved, if it doesn't exist.
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl Default for FileStore {
+    fn default() -> Self {
+        Self::new("/cache")
+    }
+}
+
+impl Store for FileStore {
+    fn load(&self, key: &str) -> std::io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.dir.join(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self, key: &str, data: &[u8]) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(key), data)
+    }
+}
+
+/// Context passed to the function registered with
+/// [`set_row_renderer`](Picker::set_row_renderer).
+#[derive(Debug)]
+pub struct RowContext<'a, T> {
+    /// The entry to render.
+    pub entry: &'a Entry<T>,
+    /// Whether the entry is selected.
+    pub selected: bool,
+    /// Whether the entry is marked, in multi-select mode.
+    pub marked: bool,
+    /// The positions (in graphemes, not characters) of the graphemes of
+    /// [`Entry::string`] which matched the query.
+    pub indices: &'a [u32],
+    /// The number of columns available.
+    pub cols: usize,
+    /// The style the picker's theme uses for matched characters.
+    pub matched: TextStyle,
+    /// How the picker renders control characters (see
+    /// [`Picker::set_control_chars`]).
+    pub control_chars: ControlChars,
+}
+
+impl<T> RowContext<'_, T> {
+    /// Renders [`Entry::string`] as a row of tab separated fields, for
+    /// entries which are matched as a whole but displayed as a table. Each
+    /// field is padded or truncated to the corresponding number of columns
+    /// in `widths` and followed by a space, and fields without a width (as
+    /// well as the last field) use as many columns as they need. Matched
+    /// characters are highlighted within the field they belong to, so the
+    /// highlighting lines up with the table regardless of padding and
+    /// truncation. The result is never wider than [`cols`](Self::cols).
+    pub fn render_fields(&self, widths: &[usize]) -> String {
+        let mut line = String::new();
+        let mut current_col = 0;
+        // the tabs between fields are graphemes too
+        let mut grapheme_idx = 0;
+        let fields: Vec<_> = self.entry.string.split('\t').collect();
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                grapheme_idx += 1;
+                if current_col >= self.cols {
+                    break;
+                }
+                line.push(' ');
+                current_col += 1;
             }
-            BareKey::Char('u') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
-                self.query.clear();
-                self.search();
-                self.selected = 0;
+            let room = self.cols - current_col;
+            let width = widths
+                .get(i)
+                .filter(|_| i + 1 < fields.len())
+                .map_or(room, |&width| width.min(room));
+            let mut used = 0;
+            let mut truncated = false;
+            for g in field.graphemes(true) {
+                let g_width = grapheme_width(g, self.control_chars);
+                truncated |= used + g_width > width;
+                if !truncated {
+                    let g = display_grapheme(g, self.control_chars);
+                    if self
+                        .indices
+                        .contains(&u32::try_from(grapheme_idx).unwrap())
+                    {
+                        write!(line, "{}", self.matched.paint(g)).unwrap();
+                    } else {
+                        line.push_str(&g);
+                    }
+                    used += g_width;
+                }
+                grapheme_idx += 1;
             }
-            BareKey::Backspace if key.has_no_modifiers() => {
-                self.query.pop();
-                self.search();
-                self.selected = 0;
+            if widths.get(i).is_some() && i + 1 < fields.len() {
+                line.extend(std::iter::repeat_n(' ', width - used));
+                used = width;
             }
-            _ => {}
+            current_col += used;
         }
-
-        None
+        line
     }
+}
+
+/// Context passed to the hook registered with
+/// [`on_after_selection_change`](Picker::on_after_selection_change).
+#[derive(Debug)]
+pub struct SelectionChange<'a, T> {
+    /// The newly selected entry.
+    pub entry: &'a Entry<T>,
+    /// The entry directly above the selected entry in the current search
+    /// results, if any.
+    pub prev: Option<&'a Entry<T>>,
+    /// The entry directly below the selected entry in the current search
+    /// results, if any.
+    pub next: Option<&'a Entry<T>>,
+}
 
-    fn handle_global_key(
+type BeforeSelectionChangeHook<T> = Box<dyn FnMut(&Entry<T>)>;
+type AfterSelectionChangeHook<T> = Box<dyn FnMut(SelectionChange<'_, T>)>;
+type CustomModeHandler<T> = Box<dyn CustomMode<T>>;
+type EntryKeyFn<T> = Box<dyn Fn(&Entry<T>) -> String>;
+type ScoreBonusFn<T> = Box<dyn Fn(&Entry<T>) -> i32>;
+type PromptFn = Box<dyn Fn(&PickerView<'_>) -> String>;
+#[cfg(feature = "preview")]
+type PreviewFn<T> = Box<dyn Fn(&Entry<T>) -> String>;
+type RowRendererFn<T> = Box<dyn Fn(&RowContext<'_, T>) -> String>;
+type SortColumnFn<T> = Box<dyn Fn(&Entry<T>) -> String>;
+type RegionRendererFn = Box<dyn Fn(usize, usize) -> String>;
+type MessageFormatterFn = Box<dyn Fn(&Message) -> Option<String>>;
+type CommandOutputFn<T> = fn(&str) -> Vec<Entry<T>>;
+type ConfigParseFn =
+    Box<dyn Fn(&mut PickerConfig, &str) -> Result<(), String>>;
+type ConfigFormatFn = Box<dyn Fn(&mut PickerConfig) -> Option<String>>;
+
+/// An additional input mode that can be registered with
+/// [`Picker::add_mode`].
+///
+/// While a custom mode is active, keys that aren't handled globally by the
+/// picker (navigation, Enter, Escape, etc) are passed to
+/// [`handle_key`](Self::handle_key), and the prompt line displays the
+/// result of [`prompt`](Self::prompt). Pressing Escape returns to normal
+/// mode, as with search mode.
+pub trait CustomMode<T> {
+    /// Returns the text to display in the prompt line while this mode is
+    /// active.
+    fn prompt(&self, picker: &Picker<T>) -> String;
+
+    /// Handles a key pressed while this mode is active. The picker can be
+    /// modified as needed, including switching to a different mode.
+    fn handle_key(
         &mut self,
+        picker: &mut Picker<T>,
         key: &KeyWithModifier,
-    ) -> Option<Response> {
-        match key.bare_key {
-            BareKey::Tab if key.has_no_modifiers() => {
-                self.down();
-            }
-            BareKey::Down if key.has_no_modifiers() => {
-                self.down();
-            }
-            BareKey::Tab if key.has_modifiers(&[KeyModifier::Shift]) => {
-                self.up();
-            }
-            BareKey::Up if key.has_no_modifiers() => {
-                self.up();
-            }
-            BareKey::Esc if key.has_no_modifiers() => {
-                self.input_mode = InputMode::Normal;
-                self.needs_redraw = true;
-            }
-            BareKey::Char('c') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
-                return Some(Response::Cancel);
+    ) -> Option<Response>;
+}
+
+/// How the case of the query affects matching (see
+/// [`PickerConfig::case_matching`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMatching {
+    /// Always respect case.
+    Respect,
+    /// Always ignore case.
+    Ignore,
+    /// Respect case if the query contains any uppercase characters, but
+    /// ignore it otherwise. This is the default.
+    #[default]
+    Smart,
+}
+
+/// An invalid value in the plugin configuration, as returned by
+/// [`PickerConfig::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The name of the option, such as `nucleo_match_mode`.
+    pub option: String,
+    /// The value the option was set to.
+    pub value: String,
+    /// A description of the values the option accepts.
+    pub expected: String,
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unrecognized value {} for option '{}': expected {}",
+            self.value, self.option, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// An option which can be set through the plugin configuration, as
+/// returned by [`Picker::describe_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOption {
+    /// The name of the option, such as `nucleo_match_mode`.
+    pub name: String,
+    /// A description of the values the option accepts, in the same form
+    /// as [`ConfigError::expected`].
+    pub expected: String,
+    /// The current value of the option, in the form it would be written in
+    /// the configuration, or `None` if it isn't set or the current setting
+    /// can't be written as a configuration value (such as a theme which
+    /// isn't one of the presets).
+    pub value: Option<String>,
+}
+
+const CASE_MATCHING_CHOICES: &[(&str, CaseMatching)] = &[
+    ("respect", CaseMatching::Respect),
+    ("ignore", CaseMatching::Ignore),
+    ("smart", CaseMatching::Smart),
+];
+
+const MATCH_MODE_CHOICES: &[(&str, MatchMode)] = &[
+    ("fuzzy", MatchMode::Fuzzy),
+    ("substring", MatchMode::Substring),
+    ("prefix", MatchMode::Prefix),
+    ("suffix", MatchMode::Suffix),
+    ("exact", MatchMode::Exact),
+];
+
+const EMPTY_SELECT_CHOICES: &[(&str, EmptySelect)] = &[
+    ("ignore", EmptySelect::Ignore),
+    ("cancel", EmptySelect::Cancel),
+    ("query", EmptySelect::Query),
+];
+
+const CURRENT_ENTRY_CHOICES: &[(&str, CurrentEntry)] = &[
+    ("show", CurrentEntry::Show),
+    ("last", CurrentEntry::Last),
+    ("hide", CurrentEntry::Hide),
+];
+
+const DIGIT_SHORTCUTS_CHOICES: &[(&str, DigitShortcuts)] = &[
+    ("enabled", DigitShortcuts::Enabled),
+    ("auto", DigitShortcuts::Auto),
+    ("disabled", DigitShortcuts::Disabled),
+];
+
+const MATCH_HIGHLIGHT_CHOICES: &[(&str, MatchHighlight)] = &[
+    ("characters", MatchHighlight::Characters),
+    ("words", MatchHighlight::Words),
+];
+
+const TIEBREAK_CHOICES: &[(&str, Tiebreak)] = &[
+    ("alphabetical", Tiebreak::Alphabetical),
+    ("natural", Tiebreak::Natural),
+    ("source", Tiebreak::SourceOrder),
+];
+
+const RANKING_PROFILE_CHOICES: &[(&str, RankingProfile)] =
+    &[("v1", RankingProfile::V1), ("v2", RankingProfile::V2)];
+
+const CONTROL_CHARS_CHOICES: &[(&str, ControlChars)] = &[
+    ("replace", ControlChars::Replace),
+    ("strip", ControlChars::Strip),
+];
+
+#[cfg(feature = "mouse")]
+const MOUSE_SELECT_CHOICES: &[(&str, MouseSelect)] = &[
+    ("double", MouseSelect::DoubleClick),
+    ("single", MouseSelect::SingleClick),
+];
+
+#[cfg(feature = "preview")]
+const PREVIEW_POSITION_CHOICES: &[(&str, PreviewPosition)] = &[
+    ("right", PreviewPosition::Right),
+    ("bottom", PreviewPosition::Bottom),
+];
+
+const PROMPT_POSITION_CHOICES: &[(&str, PromptPosition)] = &[
+    ("top", PromptPosition::Top),
+    ("bottom", PromptPosition::Bottom),
+];
+
+const LIST_DIRECTION_CHOICES: &[(&str, ListDirection)] =
+    &[("down", ListDirection::Down), ("up", ListDirection::Up)];
+
+const KEYMAP_CHOICES: &[(&str, Keymap)] = &[
+    ("default", Keymap::Default),
+    ("fzf", Keymap::Fzf),
+    ("vim", Keymap::Vim),
+    ("emacs", Keymap::Emacs),
+];
+
+const SCROLL_INDICATOR_CHOICES: &[(&str, ScrollIndicator)] = &[
+    ("none", ScrollIndicator::Hidden),
+    ("scrollbar", ScrollIndicator::Scrollbar),
+    ("position", ScrollIndicator::Position),
+];
+
+fn theme_choices() -> [(&'static str, Theme); 4] {
+    [
+        ("default", Theme::default()),
+        ("high_contrast", Theme::high_contrast()),
+        ("deuteranopia", Theme::deuteranopia()),
+        ("monochrome", Theme::monochrome()),
+    ]
+}
+
+/// The picker options which can be set through the plugin configuration.
+/// Options which are `None` leave the picker's current setting alone.
+///
+/// This is usually built from the configuration with
+/// [`parse`](Self::parse) and applied with [`Picker::configure`] (which is
+/// what [`Picker::try_load`] does), but it can also be built directly,
+/// for instance to provide defaults which the configuration can override.
+#[derive(Debug, Default, Clone)]
+pub struct PickerConfig {
+    /// `nucleo_case_matching`: `respect`, `ignore` or `smart`.
+    pub case_matching: Option<CaseMatching>,
+    /// `nucleo_match_mode`: `fuzzy`, `substring`, `prefix`, `suffix` or
+    /// `exact` (see [`Picker::set_match_mode`]).
+    pub match_mode: Option<MatchMode>,
+    /// `nucleo_match_paths` (see [`Picker::set_match_paths`]).
+    pub match_paths: Option<bool>,
+    /// `nucleo_prefer_prefix` (see [`Picker::set_prefer_prefix`]).
+    pub prefer_prefix: Option<bool>,
+    /// `nucleo_prefix_first` (see [`Picker::set_prefix_first`]).
+    pub prefix_first: Option<bool>,
+    /// `nucleo_prefer_exact_case` (see
+    /// [`Picker::set_prefer_exact_case`]).
+    pub prefer_exact_case: Option<bool>,
+    /// `nucleo_start_in_search_mode` (see [`Picker::enter_search_mode`]).
+    pub start_in_search_mode: Option<bool>,
+    /// `nucleo_remember_query` (see [`Picker::set_remember_query`]).
+    pub remember_query: Option<bool>,
+    /// `nucleo_empty_select`: `ignore`, `cancel` or `query` (see
+    /// [`Picker::set_empty_select`]).
+    pub empty_select: Option<EmptySelect>,
+    /// `nucleo_current_entry`: `show`, `last` or `hide` (see
+    /// [`Picker::set_current_entry`]).
+    pub current_entry: Option<CurrentEntry>,
+    /// `nucleo_digit_shortcuts`: `enabled`, `auto` or `disabled` (see
+    /// [`Picker::set_digit_shortcuts`]).
+    pub digit_shortcuts: Option<DigitShortcuts>,
+    /// `nucleo_match_highlight`: `characters` or `words` (see
+    /// [`Picker::set_match_highlight`]).
+    pub match_highlight: Option<MatchHighlight>,
+    /// `nucleo_score_bands`: separate strong, medium and weak matches using
+    /// the default [`ScoreBands`] (see [`Picker::set_score_bands`]).
+    pub score_bands: Option<bool>,
+    /// `nucleo_history_order` (see [`Picker::set_history_order`]).
+    pub history_order: Option<bool>,
+    /// `nucleo_frecency_weight` (see [`Picker::set_frecency_weight`]).
+    pub frecency_weight: Option<u32>,
+    /// `nucleo_recent_section` (see [`Picker::set_recent_section`]).
+    pub recent_section: Option<usize>,
+    /// `nucleo_tiebreak`: `alphabetical`, `natural` or `source` (see
+    /// [`Picker::set_tiebreak`]).
+    pub tiebreak: Option<Tiebreak>,
+    /// `nucleo_ranking_profile`: `v1` or `v2` (see
+    /// [`Picker::set_ranking_profile`]).
+    pub ranking_profile: Option<RankingProfile>,
+    /// `nucleo_control_chars`: `replace` or `strip` (see
+    /// [`Picker::set_control_chars`]).
+    pub control_chars: Option<ControlChars>,
+    /// `nucleo_ascii` (see [`Picker::set_ascii`]).
+    pub ascii: Option<bool>,
+    /// `nucleo_keep_match_visible` (see
+    /// [`Picker::set_keep_match_visible`]).
+    pub keep_match_visible: Option<bool>,
+    /// `nucleo_keep_selection_on_search` (see
+    /// [`Picker::set_keep_selection_on_search`]).
+    pub keep_selection_on_search: Option<bool>,
+    /// `nucleo_anchor_selection` (see [`Picker::set_anchor_selection`]).
+    pub anchor_selection: Option<bool>,
+    /// `nucleo_exit_search_on_navigation` (see
+    /// [`Picker::set_exit_search_on_navigation`]).
+    pub exit_search_on_navigation: Option<bool>,
+    /// `nucleo_match_any_term` (see [`Picker::set_match_any_term`]).
+    pub match_any_term: Option<bool>,
+    /// `nucleo_multi_select` (see [`Picker::enable_multi_select`]).
+    pub multi_select: Option<bool>,
+    /// `nucleo_double_esc_cancel` (see
+    /// [`Picker::set_double_esc_cancel`]).
+    pub double_esc_cancel: Option<bool>,
+    /// `nucleo_macros` (see [`Picker::set_macros`]).
+    pub macros: Option<bool>,
+    /// `nucleo_theme`: `default`, `high_contrast`, `deuteranopia` or
+    /// `monochrome` (see [`Picker::set_theme`]).
+    pub theme: Option<Theme>,
+    /// `nucleo_follow_zellij_theme` (see
+    /// [`Picker::set_follow_zellij_theme`]).
+    pub follow_zellij_theme: Option<bool>,
+    /// `nucleo_mouse_select`: `double` or `single` (see
+    /// [`Picker::set_mouse_select`]).
+    #[cfg(feature = "mouse")]
+    pub mouse_select: Option<MouseSelect>,
+    /// `nucleo_mouse` (see [`Picker::enable_mouse`]).
+    #[cfg(feature = "mouse")]
+    pub mouse: Option<bool>,
+    /// `nucleo_preview_position`: `right` or `bottom` (see
+    /// [`Picker::set_preview_position`]).
+    #[cfg(feature = "preview")]
+    pub preview_position: Option<PreviewPosition>,
+    /// `nucleo_prompt_position`: `top` or `bottom` (see
+    /// [`Picker::set_prompt_position`]).
+    pub prompt_position: Option<PromptPosition>,
+    /// `nucleo_list_direction`: `down` or `up` (see
+    /// [`Picker::set_list_direction`]).
+    pub list_direction: Option<ListDirection>,
+    /// `nucleo_placeholder` (see [`Picker::set_placeholder`]).
+    pub placeholder: Option<String>,
+    /// `nucleo_status_line` (see [`Picker::set_status_line`]).
+    pub status_line: Option<bool>,
+    /// `nucleo_keymap`: `default`, `fzf`, `vim` or `emacs` (see
+    /// [`Picker::set_keymap`]).
+    pub keymap: Option<Keymap>,
+    /// `nucleo_keybind_<action>`: a comma separated list of keys to bind
+    /// to the action (see [`Picker::bind`]). These are applied after
+    /// [`keymap`](Self::keymap).
+    pub keybindings: Vec<(KeyWithModifier, Action)>,
+    /// `nucleo_scrolloff` (see [`Picker::set_scrolloff`]).
+    pub scrolloff: Option<usize>,
+    /// `nucleo_scroll_indicator`: `none`, `scrollbar` or `position` (see
+    /// [`Picker::set_scroll_indicator`]).
+    pub scroll_indicator: Option<ScrollIndicator>,
+    /// `nucleo_pipe_name` (see [`Picker::set_pipe_name`]).
+    pub pipe_name: Option<String>,
+    /// `nucleo_prompt`: fixed text to display before the query (see
+    /// [`Picker::set_prompt`]).
+    pub prompt: Option<String>,
+    /// `nucleo_highlight_new`: a number of milliseconds or `none` (see
+    /// [`Picker::set_highlight_new`]).
+    pub highlight_new: Option<Option<std::time::Duration>>,
+    /// `nucleo_truncation_marker` (see
+    /// [`Picker::set_truncation_marker`]).
+    pub truncation_marker: Option<String>,
+    /// `nucleo_search_chunk_size`: a number or `none` (see
+    /// [`Picker::set_search_chunk_size`]).
+    pub search_chunk_size: Option<Option<usize>>,
+    /// `nucleo_coalesce_input` (see [`Picker::set_coalesce_input`]).
+    pub coalesce_input: Option<bool>,
+    /// `nucleo_result_limit`: a number or `none` (see
+    /// [`Picker::set_result_limit`]).
+    pub result_limit: Option<Option<usize>>,
+    /// `nucleo_render_slice`: a number or `none` (see
+    /// [`Picker::set_render_slice`]).
+    pub render_slice: Option<Option<usize>>,
+}
+
+impl PickerConfig {
+    /// Like [`parse`](Self::parse), but options can also be given with a
+    /// `<namespace>_` prefix (such as `files_nucleo_theme` for the
+    /// namespace `files`), which take precedence over the same options
+    /// without the prefix. This allows configuring several pickers in one
+    /// plugin separately, while options without a prefix apply to all of
+    /// them.
+    ///
+    /// A pipe name given without the prefix is prefixed with the namespace
+    /// (so `nucleo_pipe_name=picker` makes the `files` picker read from the
+    /// `files_picker` pipe), so that each picker receives its own entries.
+    pub fn parse_namespaced(
+        configuration: &std::collections::BTreeMap<String, String>,
+        namespace: &str,
+    ) -> Result<Self, ConfigError> {
+        let prefix = format!("{namespace}_");
+        let mut namespaced = configuration.clone();
+        let mut prefixed = std::collections::HashSet::new();
+        for (key, value) in configuration {
+            if let Some(option) = key.strip_prefix(&prefix) {
+                namespaced.insert(option.to_string(), value.clone());
+                prefixed.insert(option);
             }
-            BareKey::Enter if key.has_no_modifiers() => {
-                return Some(Response::Select(
-                    self.search_results[self.selected].entry,
-                ));
+        }
+        let mut config = Self::parse(&namespaced).map_err(|mut e| {
+            if prefixed.contains(e.option.as_str()) {
+                e.option.insert_str(0, &prefix);
             }
-            _ => {}
+            e
+        })?;
+        if !prefixed.contains("nucleo_pipe_name") {
+            config.pipe_name =
+                config.pipe_name.map(|name| format!("{prefix}{name}"));
         }
+        Ok(config)
+    }
 
-        None
+    /// Reads the picker options from your plugin's configuration,
+    /// returning an error for the first option with an invalid value.
+    pub fn parse(
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        for field in config_fields() {
+            let Some(value) = configuration.get(&field.name) else {
+                continue;
+            };
+            (field.parse)(&mut config, value).map_err(|value| {
+                ConfigError {
+                    option: field.name,
+                    value,
+                    expected: field.expected,
+                }
+            })?;
+        }
+        Ok(config)
     }
+}
 
-    fn down(&mut self) {
-        if self.search_results.is_empty() {
-            return;
+/// An option in the plugin configuration. Both [`PickerConfig::parse`]
+/// and [`Picker::describe_config`] are driven by the list of these
+/// returned by [`config_fields`], so that they can't disagree.
+struct ConfigField {
+    name: String,
+    expected: String,
+    // sets the field of the config, or returns the invalid part of the
+    // value
+    parse: ConfigParseFn,
+    // the value as it would be written in the configuration
+    format: ConfigFormatFn,
+}
+
+impl ConfigField {
+    fn new<V: 'static>(
+        name: &str,
+        expected: String,
+        field: fn(&mut PickerConfig) -> &mut Option<V>,
+        parse: impl Fn(&str) -> Option<V> + 'static,
+        format: impl Fn(&V) -> Option<String> + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            expected,
+            parse: Box::new(move |config, value| {
+                *field(config) =
+                    Some(parse(value).ok_or_else(|| value.to_string())?);
+                Ok(())
+            }),
+            format: Box::new(move |config| {
+                field(config).as_ref().and_then(&format)
+            }),
         }
-        self.selected = (self.search_results.len() + self.selected + 1)
-            % self.search_results.len();
-        self.needs_redraw = true;
     }
 
-    fn up(&mut self) {
-        if self.search_results.is_empty() {
-            return;
+    fn choice<V: Copy + PartialEq + 'static>(
+        name: &str,
+        choices: &[(&'static str, V)],
+        field: fn(&mut PickerConfig) -> &mut Option<V>,
+    ) -> Self {
+        let parse_choices = choices.to_vec();
+        let format_choices = choices.to_vec();
+        Self::new(
+            name,
+            choices_expected(choices),
+            field,
+            move |value| {
+                parse_choices
+                    .iter()
+                    .find(|(name, _)| *name == value)
+                    .map(|&(_, choice)| choice)
+            },
+            move |value| {
+                format_choices
+                    .iter()
+                    .find(|(_, choice)| choice == value)
+                    .map(|(name, _)| name.to_string())
+            },
+        )
+    }
+
+    fn flag(
+        name: &str,
+        field: fn(&mut PickerConfig) -> &mut Option<bool>,
+    ) -> Self {
+        Self::choice(name, &[("true", true), ("false", false)], field)
+    }
+
+    fn number<N: std::str::FromStr + ToString + 'static>(
+        name: &str,
+        field: fn(&mut PickerConfig) -> &mut Option<N>,
+    ) -> Self {
+        Self::new(
+            name,
+            "a number".to_string(),
+            field,
+            |value| value.parse().ok(),
+            |value| Some(value.to_string()),
+        )
+    }
+
+    fn limit(
+        name: &str,
+        field: fn(&mut PickerConfig) -> &mut Option<Option<usize>>,
+    ) -> Self {
+        Self::new(
+            name,
+            "a number or 'none'".to_string(),
+            field,
+            |value| match value {
+                "none" => Some(None),
+                _ => value.parse().ok().map(Some),
+            },
+            |value| {
+                Some(
+                    value.map_or_else(
+                        || "none".to_string(),
+                        |n| n.to_string(),
+                    ),
+                )
+            },
+        )
+    }
+
+    fn text(
+        name: &str,
+        field: fn(&mut PickerConfig) -> &mut Option<String>,
+    ) -> Self {
+        Self::new(
+            name,
+            "any text".to_string(),
+            field,
+            |value| Some(value.to_string()),
+            |value| Some(value.clone()),
+        )
+    }
+
+    fn keybind(action: Action) -> Self {
+        Self {
+            name: format!("nucleo_keybind_{}", action.name()),
+            expected: "a comma separated list of keys".to_string(),
+            parse: Box::new(move |config, value| {
+                for key in value.split(',').map(str::trim) {
+                    let key = key.parse().map_err(|_| key.to_string())?;
+                    config.keybindings.push((key, action));
+                }
+                Ok(())
+            }),
+            format: Box::new(move |config| {
+                let keys: Vec<_> = config
+                    .keybindings
+                    .iter()
+                    .filter(|(_, bound)| *bound == action)
+                    .map(|(key, _)| key.to_string())
+                    .collect();
+                (!keys.is_empty()).then(|| keys.join(", "))
+            }),
         }
-        self.selected = (self.search_results.len() + self.selected - 1)
-            % self.search_results.len();
-        self.needs_redraw = true;
     }
 }
 
-#[derive(Debug)]
-struct SearchResult {
-    entry: usize,
-    score: u32,
-    indices: Vec<u32>,
+fn config_fields() -> Vec<ConfigField> {
+    let mut fields = vec![
+        ConfigField::choice(
+            "nucleo_case_matching",
+            CASE_MATCHING_CHOICES,
+            |config| &mut config.case_matching,
+        ),
+        ConfigField::choice(
+            "nucleo_match_mode",
+            MATCH_MODE_CHOICES,
+            |config| &mut config.match_mode,
+        ),
+        ConfigField::flag("nucleo_match_paths", |config| {
+            &mut config.match_paths
+        }),
+        ConfigField::flag("nucleo_prefer_prefix", |config| {
+            &mut config.prefer_prefix
+        }),
+        ConfigField::flag("nucleo_prefix_first", |config| {
+            &mut config.prefix_first
+        }),
+        ConfigField::flag("nucleo_prefer_exact_case", |config| {
+            &mut config.prefer_exact_case
+        }),
+        ConfigField::flag("nucleo_start_in_search_mode", |config| {
+            &mut config.start_in_search_mode
+        }),
+        ConfigField::flag("nucleo_remember_query", |config| {
+            &mut config.remember_query
+        }),
+        ConfigField::choice(
+            "nucleo_empty_select",
+            EMPTY_SELECT_CHOICES,
+            |config| &mut config.empty_select,
+        ),
+        ConfigField::choice(
+            "nucleo_current_entry",
+            CURRENT_ENTRY_CHOICES,
+            |config| &mut config.current_entry,
+        ),
+        ConfigField::choice(
+            "nucleo_digit_shortcuts",
+            DIGIT_SHORTCUTS_CHOICES,
+            |config| &mut config.digit_shortcuts,
+        ),
+        ConfigField::choice(
+            "nucleo_match_highlight",
+            MATCH_HIGHLIGHT_CHOICES,
+            |config| &mut config.match_highlight,
+        ),
+        ConfigField::flag("nucleo_score_bands", |config| {
+            &mut config.score_bands
+        }),
+        ConfigField::flag("nucleo_history_order", |config| {
+            &mut config.history_order
+        }),
+        ConfigField::number("nucleo_frecency_weight", |config| {
+            &mut config.frecency_weight
+        }),
+        ConfigField::number("nucleo_recent_section", |config| {
+            &mut config.recent_section
+        }),
+        ConfigField::choice("nucleo_tiebreak", TIEBREAK_CHOICES, |config| {
+            &mut config.tiebreak
+        }),
+        ConfigField::choice(
+            "nucleo_ranking_profile",
+            RANKING_PROFILE_CHOICES,
+            |config| &mut config.ranking_profile,
+        ),
+        ConfigField::choice(
+            "nucleo_control_chars",
+            CONTROL_CHARS_CHOICES,
+            |config| &mut config.control_chars,
+        ),
+        ConfigField::flag("nucleo_ascii", |config| &mut config.ascii),
+        ConfigField::flag("nucleo_keep_match_visible", |config| {
+            &mut config.keep_match_visible
+        }),
+        ConfigField::flag("nucleo_keep_selection_on_search", |config| {
+            &mut config.keep_selection_on_search
+        }),
+        ConfigField::flag("nucleo_anchor_selection", |config| {
+            &mut config.anchor_selection
+        }),
+        ConfigField::flag("nucleo_exit_search_on_navigation", |config| {
+            &mut config.exit_search_on_navigation
+        }),
+        ConfigField::flag("nucleo_match_any_term", |config| {
+            &mut config.match_any_term
+        }),
+        ConfigField::flag("nucleo_multi_select", |config| {
+            &mut config.multi_select
+        }),
+        ConfigField::flag("nucleo_double_esc_cancel", |config| {
+            &mut config.double_esc_cancel
+        }),
+        ConfigField::flag("nucleo_macros", |config| &mut config.macros),
+        ConfigField::choice("nucleo_theme", &theme_choices(), |config| {
+            &mut config.theme
+        }),
+        ConfigField::flag("nucleo_follow_zellij_theme", |config| {
+            &mut config.follow_zellij_theme
+        }),
+        #[cfg(feature = "mouse")]
+        ConfigField::choice(
+            "nucleo_mouse_select",
+            MOUSE_SELECT_CHOICES,
+            |config| &mut config.mouse_select,
+        ),
+        #[cfg(feature = "mouse")]
+        ConfigField::flag("nucleo_mouse", |config| &mut config.mouse),
+        #[cfg(feature = "preview")]
+        ConfigField::choice(
+            "nucleo_preview_position",
+            PREVIEW_POSITION_CHOICES,
+            |config| &mut config.preview_position,
+        ),
+        ConfigField::choice(
+            "nucleo_prompt_position",
+            PROMPT_POSITION_CHOICES,
+            |config| &mut config.prompt_position,
+        ),
+        ConfigField::choice(
+            "nucleo_list_direction",
+            LIST_DIRECTION_CHOICES,
+            |config| &mut config.list_direction,
+        ),
+        ConfigField::text("nucleo_placeholder", |config| {
+            &mut config.placeholder
+        }),
+        ConfigField::flag("nucleo_status_line", |config| {
+            &mut config.status_line
+        }),
+        ConfigField::choice("nucleo_keymap", KEYMAP_CHOICES, |config| {
+            &mut config.keymap
+        }),
+    ];
+    fields.extend(
+        Action::ALL
+            .iter()
+            .map(|&action| ConfigField::keybind(action)),
+    );
+    fields.extend([
+        ConfigField::number("nucleo_scrolloff", |config| {
+            &mut config.scrolloff
+        }),
+        ConfigField::choice(
+            "nucleo_scroll_indicator",
+            SCROLL_INDICATOR_CHOICES,
+            |config| &mut config.scroll_indicator,
+        ),
+        ConfigField::text("nucleo_pipe_name", |config| &mut config.pipe_name),
+        ConfigField::text("nucleo_prompt", |config| &mut config.prompt),
+        ConfigField::new(
+            "nucleo_highlight_new",
+            "a number or 'none'".to_string(),
+            |config| &mut config.highlight_new,
+            |value| match value {
+                "none" => Some(None),
+                _ => value.parse().ok().map(|millis| {
+                    Some(std::time::Duration::from_millis(millis))
+                }),
+            },
+            |value| {
+                Some(value.map_or_else(
+                    || "none".to_string(),
+                    |duration| duration.as_millis().to_string(),
+                ))
+            },
+        ),
+        ConfigField::text("nucleo_truncation_marker", |config| {
+            &mut config.truncation_marker
+        }),
+        ConfigField::limit("nucleo_search_chunk_size", |config| {
+            &mut config.search_chunk_size
+        }),
+        ConfigField::flag("nucleo_coalesce_input", |config| {
+            &mut config.coalesce_input
+        }),
+        ConfigField::limit("nucleo_result_limit", |config| {
+            &mut config.result_limit
+        }),
+        ConfigField::limit("nucleo_render_slice", |config| {
+            &mut config.render_slice
+        }),
+    ]);
+    fields
 }
 
-#[derive(Debug)]
-struct SearchResultWithString<'a> {
-    score: u32,
-    first_index: Option<u32>,
-    string: &'a str,
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    #[default]
+    Normal,
+    Search,
+    Custom(usize),
 }
 
-impl Ord for SearchResultWithString<'_> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.score
-            .cmp(&other.score)
-            .reverse()
-            .then_with(|| self.first_index.cmp(&other.first_index))
-            .then_with(|| self.string.cmp(other.string))
-    }
-}
+/// State of the picker itself.
+#[derive(Default)]
+pub struct Picker<T> {
+    query: String,
+    cursor: usize,
+    query_selected: bool,
+    remember_query: bool,
+    remembered_query: Option<String>,
+    unfocused: bool,
+    config_namespace: Option<String>,
+    skip_remembered_query: bool,
+    closed: bool,
+    all_entries: Vec<Entry<T>>,
+    hidden: std::collections::HashSet<usize>,
+    search_results: Vec<SearchResult>,
+    headers: Vec<(usize, Heading)>,
+    results_generation: u64,
+    row_cache: RowCache,
+    selected: usize,
+    input_mode: InputMode,
+    showing_help: bool,
+    requires_permissions: bool,
+    permission_denied: bool,
+    number_prompt: Option<NumberPrompt>,
+    message_formatter: Option<MessageFormatterFn>,
+    pending_jump: bool,
+    jumplist: Vec<String>,
+    jumplist_position: usize,
+    multi_select: bool,
+    mark_key: Option<KeyWithModifier>,
+    keymap: Keymap,
+    keybindings: Vec<(KeyWithModifier, Option<Action>)>,
+    action_keys: Vec<(KeyWithModifier, String)>,
+    #[cfg(feature = "mouse")]
+    mouse: bool,
+    pipe_name: Option<String>,
+    pipe_output: Option<String>,
+    #[cfg(feature = "mouse")]
+    mouse_select: MouseSelect,
+    #[cfg(feature = "mouse")]
+    last_click: Option<(usize, std::time::Instant)>,
+    theme: Theme,
+    follow_zellij_theme: bool,
+    double_esc_cancel: bool,
+    esc_pressed_at: Option<std::time::Instant>,
+    macros: bool,
+    refreshing: bool,
+    list_area: ListArea,
+    render_slice: Option<usize>,
+    rows_deferred: bool,
+    fill_deferred_rows: bool,
+    #[cfg(feature = "mouse")]
+    drag: Option<(usize, Vec<String>)>,
+    marks: Vec<String>,
+    empty_select: EmptySelect,
+    current_entry: CurrentEntry,
+    digit_shortcuts: DigitShortcuts,
+    match_highlight: MatchHighlight,
+    score_bands: Option<ScoreBands>,
+    tiebreak: Tiebreak,
+    ranking_profile: RankingProfile,
+    prefix_first: bool,
+    prefer_exact_case: bool,
+    control_chars: ControlChars,
+    ascii: bool,
+    keep_match_visible: bool,
+    keep_selection_on_search: bool,
+    anchor_selection: bool,
+    exit_search_on_navigation: bool,
+    match_any_term: bool,
+    truncation_marker: TruncationMarker,
+    result_limit: Option<usize>,
+    result_pages: usize,
+    match_count: usize,
+    metrics: Metrics,
+    batch: Batch,
+    #[cfg(feature = "telemetry")]
+    started_at: Option<std::time::Instant>,
+    #[cfg(feature = "telemetry")]
+    keystrokes: usize,
+    #[cfg(feature = "telemetry")]
+    telemetry: Option<Telemetry>,
+    history: Vec<String>,
+    history_order: bool,
+    usage: std::collections::HashMap<String, Usage>,
+    frecency_weight: u32,
+    pinned: std::collections::HashSet<String>,
+    store: Option<Box<dyn Store>>,
+    highlight_new: Option<std::time::Duration>,
+    known_keys: std::collections::HashSet<String>,
+    new_entries: std::collections::HashMap<String, std::time::Instant>,
+    source_styles: std::collections::HashMap<String, TextStyle>,
+    source_priorities: std::collections::HashMap<String, i32>,
+    source_limits: std::collections::HashMap<String, usize>,
+    recent_section: usize,
+    recent_count: usize,
+    custom_modes: Vec<(String, Option<CustomModeHandler<T>>)>,
+    recording: Option<Vec<KeyWithModifier>>,
+    recorded_macro: Vec<KeyWithModifier>,
+    needs_redraw: bool,
 
-impl PartialOrd for SearchResultWithString<'_> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
+    pattern: nucleo_matcher::pattern::Pattern,
+    matcher: nucleo_matcher::Matcher,
+    haystack_buf: Vec<char>,
+    searched_query: String,
+    pending_search: Option<PendingSearch>,
+    search_chunk_size: Option<usize>,
+    search_timer_set: bool,
+    coalesce_input: bool,
+    query_edited: bool,
+    case_matching: nucleo_matcher::pattern::CaseMatching,
+    match_mode: MatchMode,
+    match_paths: bool,
+    start_in_search_mode: bool,
 
-impl Eq for SearchResultWithString<'_> {}
+    reported_selection: Option<(usize, String)>,
+    reported_rank: Option<(usize, u64)>,
+    pending_selection: Option<String>,
+    entry_key: Option<EntryKeyFn<T>>,
+    score_bonus: Option<ScoreBonusFn<T>>,
+    prompt: Option<PromptFn>,
+    #[cfg(feature = "preview")]
+    preview: Option<PreviewFn<T>>,
+    row_renderer: Option<RowRendererFn<T>>,
+    sort_columns: Vec<(String, SortColumnFn<T>)>,
+    sort: Option<(usize, SortDirection)>,
+    pending_sort: bool,
+    #[cfg(feature = "preview")]
+    preview_position: PreviewPosition,
+    prompt_position: PromptPosition,
+    list_direction: ListDirection,
+    placeholder: Placeholder,
+    status_line: bool,
+    size: Option<(usize, usize)>,
+    scroll_offset: usize,
+    scrolloff: usize,
+    scroll_indicator: ScrollIndicator,
+    before_selection_change: Option<BeforeSelectionChangeHook<T>>,
+    after_selection_change: Option<AfterSelectionChangeHook<T>>,
+    command_source: Option<CommandSource<T>>,
+}
 
-impl PartialEq for SearchResultWithString<'_> {
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other) == std::cmp::Ordering::Equal
+impl<T> Picker<T> {
+    /// This function must be called during your plugin's
+    /// [`load`](zellij_tile::ZellijPlugin::load) function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the picker options in the configuration have
+    /// invalid values. Use [`try_load`](Self::try_load) to handle these
+    /// errors instead.
+    pub fn load(
+        &mut self,
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) {
+        if let Err(e) = self.try_load(configuration) {
+            panic!("{e}");
+        }
+    }
+
+    /// Like [`load`](Self::load), but returns an error rather than
+    /// panicking if any of the picker options in the configuration have
+    /// invalid values. In that case, none of the options are applied.
+    /// Options are read from namespaced keys if
+    /// [`set_config_namespace`](Self::set_config_namespace) was called.
+    pub fn try_load(
+        &mut self,
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) -> Result<(), ConfigError> {
+        let config = if let Some(namespace) = &self.config_namespace {
+            PickerConfig::parse_namespaced(configuration, namespace)?
+        } else {
+            PickerConfig::parse(configuration)?
+        };
+        subscribe(PICKER_EVENTS);
+        if config.remember_query == Some(true) {
+            subscribe(&[EventType::Visible]);
+        }
+        self.metrics.renders = 0;
+        #[cfg(feature = "telemetry")]
+        {
+            self.started_at = Some(std::time::Instant::now());
+        }
+        self.configure(config);
+        Ok(())
+    }
+
+    /// Applies the options which are set in the given configuration.
+    pub fn configure(&mut self, config: PickerConfig) {
+        match config.case_matching {
+            Some(CaseMatching::Respect) => self.use_case_matching_respect(),
+            Some(CaseMatching::Ignore) => self.use_case_matching_ignore(),
+            Some(CaseMatching::Smart) => self.use_case_matching_smart(),
+            None => {}
+        }
+        if let Some(match_mode) = config.match_mode {
+            self.set_match_mode(match_mode);
+        }
+        match config.match_paths {
+            Some(true) => self.set_match_paths(),
+            Some(false) => self.clear_match_paths(),
+            None => {}
+        }
+        if let Some(prefer_prefix) = config.prefer_prefix {
+            self.set_prefer_prefix(prefer_prefix);
+        }
+        if let Some(prefix_first) = config.prefix_first {
+            self.set_prefix_first(prefix_first);
+        }
+        if let Some(prefer_exact_case) = config.prefer_exact_case {
+            self.set_prefer_exact_case(prefer_exact_case);
+        }
+        if let Some(start_in_search_mode) = config.start_in_search_mode {
+            self.start_in_search_mode = start_in_search_mode;
+            if start_in_search_mode {
+                self.enter_search_mode();
+            } else {
+                self.enter_normal_mode();
+            }
+        }
+        if let Some(remember_query) = config.remember_query {
+            self.set_remember_query(remember_query);
+        }
+        if let Some(empty_select) = config.empty_select {
+            self.set_empty_select(empty_select);
+        }
+        if let Some(current_entry) = config.current_entry {
+            self.set_current_entry(current_entry);
+        }
+        if let Some(digit_shortcuts) = config.digit_shortcuts {
+            self.set_digit_shortcuts(digit_shortcuts);
+        }
+        if let Some(match_highlight) = config.match_highlight {
+            self.set_match_highlight(match_highlight);
+        }
+        if let Some(score_bands) = config.score_bands {
+            self.set_score_bands(score_bands.then(ScoreBands::default));
+        }
+        if let Some(history_order) = config.history_order {
+            self.set_history_order(history_order);
+        }
+        if let Some(weight) = config.frecency_weight {
+            self.set_frecency_weight(weight);
+        }
+        if let Some(count) = config.recent_section {
+            self.set_recent_section(count);
+        }
+        if let Some(tiebreak) = config.tiebreak {
+            self.set_tiebreak(tiebreak);
+        }
+        if let Some(ranking_profile) = config.ranking_profile {
+            self.set_ranking_profile(ranking_profile);
+        }
+        if let Some(control_chars) = config.control_chars {
+            self.set_control_chars(control_chars);
+        }
+        if let Some(ascii) = config.ascii {
+            self.set_ascii(ascii);
+        }
+        if let Some(keep) = config.keep_match_visible {
+            self.set_keep_match_visible(keep);
+        }
+        if let Some(keep) = config.keep_selection_on_search {
+            self.set_keep_selection_on_search(keep);
+        }
+        if let Some(anchor) = config.anchor_selection {
+            self.set_anchor_selection(anchor);
+        }
+        if let Some(exit) = config.exit_search_on_navigation {
+            self.set_exit_search_on_navigation(exit);
+        }
+        if let Some(match_any_term) = config.match_any_term {
+            self.set_match_any_term(match_any_term);
+        }
+        match config.multi_select {
+            Some(true) => self.enable_multi_select(),
+            Some(false) => self.disable_multi_select(),
+            None => {}
+        }
+        if let Some(double_esc_cancel) = config.double_esc_cancel {
+            self.set_double_esc_cancel(double_esc_cancel);
+        }
+        if let Some(macros) = config.macros {
+            self.set_macros(macros);
+        }
+        if let Some(theme) = config.theme {
+            self.set_theme(theme);
+        }
+        if let Some(follow) = config.follow_zellij_theme {
+            self.set_follow_zellij_theme(follow);
+        }
+        #[cfg(feature = "mouse")]
+        if let Some(mouse_select) = config.mouse_select {
+            self.set_mouse_select(mouse_select);
+        }
+        #[cfg(feature = "mouse")]
+        match config.mouse {
+            Some(true) => self.enable_mouse(),
+            Some(false) => self.disable_mouse(),
+            None => {}
+        }
+        #[cfg(feature = "preview")]
+        if let Some(position) = config.preview_position {
+            self.set_preview_position(position);
+        }
+        if let Some(position) = config.prompt_position {
+            self.set_prompt_position(position);
+        }
+        if let Some(direction) = config.list_direction {
+            self.set_list_direction(direction);
+        }
+        if let Some(placeholder) = config.placeholder {
+            self.set_placeholder(placeholder);
+        }
+        if let Some(status_line) = config.status_line {
+            self.set_status_line(status_line);
+        }
+        if let Some(keymap) = config.keymap {
+            self.set_keymap(keymap);
+        }
+        for (key, action) in config.keybindings {
+            self.bind(key, action);
+        }
+        if let Some(scrolloff) = config.scrolloff {
+            self.set_scrolloff(scrolloff);
+        }
+        if let Some(scroll_indicator) = config.scroll_indicator {
+            self.set_scroll_indicator(scroll_indicator);
+        }
+        if let Some(name) = config.pipe_name {
+            self.set_pipe_name(name);
+        }
+        if let Some(prompt) = config.prompt {
+            self.set_prompt(move |_| prompt.clone());
+        }
+        if let Some(duration) = config.highlight_new {
+            self.set_highlight_new(duration);
+        }
+        if let Some(marker) = config.truncation_marker {
+            self.set_truncation_marker(marker);
+        }
+        if let Some(chunk_size) = config.search_chunk_size {
+            self.set_search_chunk_size(chunk_size);
+        }
+        if let Some(coalesce) = config.coalesce_input {
+            self.set_coalesce_input(coalesce);
+        }
+        if let Some(limit) = config.result_limit {
+            self.set_result_limit(limit);
+        }
+        if let Some(rows) = config.render_slice {
+            self.set_render_slice(rows);
+        }
+    }
+
+    /// Returns every option which can be set through the plugin
+    /// configuration, along with the values it accepts and the value it
+    /// currently has, for instance to display a settings screen or to
+    /// check a configuration before applying it.
+    pub fn describe_config(&self) -> Vec<ConfigOption> {
+        let mut config = self.current_config();
+        config_fields()
+            .into_iter()
+            .map(|field| ConfigOption {
+                value: (field.format)(&mut config),
+                name: field.name,
+                expected: field.expected,
+            })
+            .collect()
+    }
+
+    // the options which would configure a picker like this one, for
+    // describe_config
+    fn current_config(&self) -> PickerConfig {
+        PickerConfig {
+            case_matching: Some(match self.case_matching {
+                nucleo_matcher::pattern::CaseMatching::Respect => {
+                    CaseMatching::Respect
+                }
+                nucleo_matcher::pattern::CaseMatching::Ignore => {
+                    CaseMatching::Ignore
+                }
+                _ => CaseMatching::Smart,
+            }),
+            match_mode: Some(self.match_mode),
+            match_paths: Some(self.match_paths),
+            prefer_prefix: Some(self.matcher.config.prefer_prefix),
+            prefix_first: Some(self.prefix_first),
+            prefer_exact_case: Some(self.prefer_exact_case),
+            start_in_search_mode: Some(self.start_in_search_mode),
+            remember_query: Some(self.remember_query),
+            empty_select: Some(self.empty_select),
+            current_entry: Some(self.current_entry),
+            digit_shortcuts: Some(self.digit_shortcuts),
+            match_highlight: Some(self.match_highlight),
+            score_bands: Some(self.score_bands.is_some()),
+            history_order: Some(self.history_order),
+            frecency_weight: Some(self.frecency_weight),
+            recent_section: Some(self.recent_section),
+            tiebreak: Some(self.tiebreak),
+            ranking_profile: Some(self.ranking_profile),
+            control_chars: Some(self.control_chars),
+            ascii: Some(self.ascii),
+            keep_match_visible: Some(self.keep_match_visible),
+            keep_selection_on_search: Some(self.keep_selection_on_search),
+            anchor_selection: Some(self.anchor_selection),
+            exit_search_on_navigation: Some(self.exit_search_on_navigation),
+            match_any_term: Some(self.match_any_term),
+            multi_select: Some(self.multi_select),
+            double_esc_cancel: Some(self.double_esc_cancel),
+            macros: Some(self.macros),
+            theme: Some(self.theme),
+            follow_zellij_theme: Some(self.follow_zellij_theme),
+            #[cfg(feature = "mouse")]
+            mouse_select: Some(self.mouse_select),
+            #[cfg(feature = "mouse")]
+            mouse: Some(self.mouse),
+            #[cfg(feature = "preview")]
+            preview_position: Some(self.preview_position),
+            prompt_position: Some(self.prompt_position),
+            list_direction: Some(self.list_direction),
+            placeholder: Some(self.placeholder.0.clone()),
+            status_line: Some(self.status_line),
+            keymap: Some(self.keymap),
+            keybindings: self
+                .keybindings
+                .iter()
+                .filter_map(|(key, action)| Some((key.clone(), (*action)?)))
+                .collect(),
+            scrolloff: Some(self.scrolloff),
+            scroll_indicator: Some(self.scroll_indicator),
+            pipe_name: self.pipe_name.clone(),
+            // the prompt may have been set to a function, which has no
+            // value to show
+            prompt: None,
+            highlight_new: Some(self.highlight_new),
+            truncation_marker: Some(self.truncation_marker.0.clone()),
+            search_chunk_size: Some(self.search_chunk_size),
+            coalesce_input: Some(self.coalesce_input),
+            result_limit: Some(self.result_limit),
+            render_slice: Some(self.render_slice),
+        }
+    }
+
+    /// This function must be called during your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function. If an entry
+    /// was selected or the picker was closed, this function will return a
+    /// [`Response`]. This function will update the picker's internal state
+    /// of whether it needs to redraw the picker, so your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function should return
+    /// true if [`needs_redraw`](Self::needs_redraw) returns true.
+    ///
+    /// Changes to the entries or the query made after this returns (such
+    /// as calling [`clear`](Self::clear) and [`extend`](Self::extend) while
+    /// handling the same event) are applied as a [`batch`](Self::batch),
+    /// which finishes the next time the results are needed: on the next
+    /// call to [`render`](Self::render) or to this function, or an explicit
+    /// call to [`flush`](Self::flush). This means that the entries are only
+    /// searched once, and that the selection is restored afterwards. Until
+    /// then, [`results`](Self::results) is empty.
+    pub fn update(&mut self, event: &Event) -> Option<Response> {
+        #[cfg(feature = "telemetry")]
+        {
+            self.started_at.get_or_insert_with(std::time::Instant::now);
+            if let Event::Key(_) = event {
+                self.keystrokes += 1;
+            }
+        }
+
+        self.finish_update_batch();
+        if !matches!(event, Event::Key(key) if self.only_edits_query(key)) {
+            self.flush();
+        }
+        let response = match event {
+            Event::Key(_) if self.unfocused => None,
+            #[cfg(feature = "mouse")]
+            Event::Mouse(_) if self.unfocused => None,
+            Event::Key(key) => self.handle_key(key),
+            #[cfg(feature = "mouse")]
+            Event::Mouse(mouse) if self.mouse => self.handle_mouse(mouse),
+            Event::ModeUpdate(mode_info) if self.follow_zellij_theme => {
+                self.set_theme(Theme::from_palette(&mode_info.style.colors));
+                None
+            }
+            Event::PermissionRequestResult(status)
+                if self.requires_permissions =>
+            {
+                self.permission_denied =
+                    matches!(status, PermissionStatus::Denied);
+                self.needs_redraw = true;
+                self.permission_denied.then_some(Response::PermissionDenied)
+            }
+            Event::Visible(true) if self.remember_query && self.closed => {
+                self.open();
+                None
+            }
+            Event::RunCommandResult(_, stdout, _, context) => {
+                self.handle_command_output(stdout, context);
+                None
+            }
+            Event::Timer(_) => {
+                self.continue_search();
+                if self.rows_deferred {
+                    self.rows_deferred = false;
+                    self.fill_deferred_rows = true;
+                    self.needs_redraw = true;
+                }
+                if self
+                    .command_source
+                    .as_ref()
+                    .and_then(|command_source| command_source.due)
+                    .is_some_and(|due| due <= std::time::Instant::now())
+                {
+                    self.run_command_source();
+                }
+                self.expire_new_entries();
+                if self.esc_pressed_at.is_some_and(|pressed_at| {
+                    pressed_at.elapsed() >= DOUBLE_ESC_TIMEOUT
+                }) {
+                    self.esc_pressed_at = None;
+                    self.needs_redraw = true;
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some(Response::Select { index, .. }) = response {
+            let string = self.all_entries[index].string.to_string();
+            self.record_history(string);
+        }
+        if let Some(
+            Response::Select { .. }
+            | Response::SelectMulti(_)
+            | Response::Query(_)
+            | Response::Cancel,
+        ) = response
+        {
+            self.closed = true;
+            if self.remember_query
+                && !matches!(response, Some(Response::Cancel))
+            {
+                self.remembered_query = Some(self.query.clone());
+            }
+        }
+        match &response {
+            Some(Response::Select { index, .. }) => {
+                self.track_use(*index);
+                self.save_state();
+            }
+            Some(Response::SelectMulti(indices)) => {
+                for &index in indices {
+                    self.track_use(index);
+                }
+                self.save_state();
+            }
+            _ => {}
+        }
+        #[cfg(feature = "telemetry")]
+        if response.is_some() {
+            self.telemetry = Some(Telemetry {
+                elapsed: self
+                    .started_at
+                    .take()
+                    .map_or_else(Default::default, |started_at| {
+                        started_at.elapsed()
+                    }),
+                keystrokes: std::mem::take(&mut self.keystrokes),
+                query: self.query.clone(),
+            });
+        }
+        // the picker stays open while the entries are reloaded
+        if let Some(response) = response
+            .as_ref()
+            .filter(|response| !matches!(response, Response::Refresh))
+        {
+            if let Some(pipe_id) = self.pipe_output.take() {
+                self.write_pipe_output(&pipe_id, response);
+            }
+        }
+        self.report_selection_change();
+        self.batch.after_update = true;
+        response
+    }
+
+    /// This function must be called during your plugin's
+    /// [`render`](zellij_tile::ZellijPlugin::render) function.
+    pub fn render(&mut self, rows: usize, cols: usize) {
+        print!("{}", self.render_to_string(rows, cols));
+    }
+
+    /// Renders the picker like [`render`](Self::render), but returns the
+    /// output rather than printing it. This allows testing what the picker
+    /// displays without running inside zellij (see the [`testing`]
+    /// module).
+    pub fn render_to_string(&mut self, rows: usize, cols: usize) -> String {
+        self.flush();
+
+        let mut screen = String::new();
+        if rows == 0 {
+            return screen;
+        }
+
+        self.metrics.renders += 1;
+
+        if self.size != Some((rows, cols)) {
+            // the pane was resized, so make sure the layout computed below
+            // starts from a valid selection rather than one that only made
+            // sense at the previous size
+            self.size = Some((rows, cols));
+            self.selected = self
+                .selected
+                .min(self.search_results.len().saturating_sub(1));
+        }
+
+        if self.showing_help {
+            let lines: Vec<_> =
+                self.help_lines().into_iter().take(rows).collect();
+            screen.push_str(&lines.join("\n"));
+            self.needs_redraw = false;
+            return screen;
+        }
+
+        #[cfg(feature = "preview")]
+        let preview = self.preview.as_ref().map(|preview| {
+            self.search_results
+                .get(self.selected)
+                .map(|search_result| {
+                    preview(&self.all_entries[search_result.entry])
+                })
+                .unwrap_or_default()
+        });
+        let footer = self.footer(cols).filter(|_| rows > 1);
+        let status_line = self.status_line().filter(|_| rows > 2);
+        let sort_header = self
+            .sort_header(cols)
+            .filter(|_| rows > 2 + usize::from(status_line.is_some()));
+        let body_rows = rows
+            - 1
+            - usize::from(footer.is_some())
+            - usize::from(status_line.is_some())
+            - usize::from(sort_header.is_some());
+        #[cfg(feature = "preview")]
+        let (list_rows, list_cols) = match (&preview, self.preview_position) {
+            (None, _) => (body_rows, cols),
+            (Some(_), PreviewPosition::Right) => (body_rows, cols / 2),
+            (Some(_), PreviewPosition::Bottom) => (body_rows / 2, cols),
+        };
+        #[cfg(not(feature = "preview"))]
+        let (list_rows, list_cols) = (body_rows, cols);
+
+        // the headers only change along with the search results, so moving
+        // the selection through a long list doesn't need to look at all of
+        // the results again
+        let headers = std::mem::take(&mut self.headers);
+        let selected_row = self.selected
+            + headers
+                .iter()
+                .filter(|(rank, _)| *rank <= self.selected)
+                .count();
+        let total_rows = self.search_results.len() + headers.len();
+        // keep the headers directly above the selected entry visible
+        let first_row = selected_row
+            - headers
+                .iter()
+                .filter(|(rank, _)| *rank == self.selected)
+                .count();
+        let visible_rows_start =
+            self.scroll_to(first_row, selected_row, total_rows, list_rows);
+        let scrollbar = self.scroll_indicator == ScrollIndicator::Scrollbar
+            && total_rows > list_rows
+            && list_cols > 1;
+        let entry_cols = list_cols - usize::from(scrollbar);
+        let visible_rows =
+            self.list_rows(&headers, visible_rows_start, list_rows);
+
+        // when the list is taller than the render slice, only the rows at
+        // the top of the list, the selected row and the rows which can be
+        // reused from the previous frame are rendered now, and the rest
+        // are filled in once the timer fires
+        let deferred_from = self
+            .render_slice
+            .filter(|&slice| {
+                visible_rows.len() > slice && !self.fill_deferred_rows
+            })
+            .unwrap_or(usize::MAX);
+        self.fill_deferred_rows = false;
+        let mut left_blank = false;
+
+        // rows which look the same as in the previous frame (which is most
+        // of them, while only the selection moves) are reused rather than
+        // rendered again
+        let row_style = self.row_style(entry_cols);
+        let mut row_cache = std::mem::take(&mut self.row_cache);
+        if row_cache.generation != self.results_generation
+            || row_cache.style.as_ref() != Some(&row_style)
+            || self.row_renderer.is_some()
+            || !self.new_entries.is_empty()
+        {
+            row_cache.lines.clear();
+        }
+        let mut cached_lines = std::collections::HashMap::new();
+
+        let selected_row = selected_row - visible_rows_start;
+        let mut list_lines: Vec<_> = visible_rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut line = String::new();
+                let mut width = if let ListRow::Entry(rank) = *row {
+                    let key =
+                        (rank, rank == self.selected, self.is_marked(rank));
+                    let deferred = i >= deferred_from && i != selected_row;
+                    let cached = row_cache.lines.remove(&key).or_else(|| {
+                        (!deferred).then(|| {
+                            let mut line = String::new();
+                            let width =
+                                self.render_row(&mut line, row, entry_cols);
+                            (line, width)
+                        })
+                    });
+                    if let Some((cached, width)) = cached {
+                        line.push_str(&cached);
+                        cached_lines.insert(key, (cached, width));
+                        width
+                    } else {
+                        left_blank = true;
+                        0
+                    }
+                } else {
+                    self.render_row(&mut line, row, entry_cols)
+                };
+                if scrollbar {
+                    self.push_scrollbar(
+                        &mut line, width, entry_cols, i, total_rows,
+                        list_rows,
+                    );
+                    width = list_cols;
+                }
+                (line, width, row.rank())
+            })
+            .collect();
+        if left_blank && !self.rows_deferred {
+            set_timeout(0.0);
+            self.rows_deferred = true;
+        }
+        if self.list_direction == ListDirection::Up {
+            // the first result goes at the bottom of the list, so the empty
+            // space is at the top
+            list_lines.resize_with(list_rows, || (String::new(), 0, None));
+            list_lines.reverse();
+        }
+
+        let before_list = match self.prompt_position {
+            PromptPosition::Top => {
+                1 + usize::from(status_line.is_some())
+                    + usize::from(sort_header.is_some())
+            }
+            PromptPosition::Bottom => usize::from(footer.is_some()),
+        };
+        self.row_cache = RowCache {
+            generation: self.results_generation,
+            style: Some(row_style),
+            lines: cached_lines,
+        };
+
+        self.list_area = ListArea {
+            top: before_list,
+            ranks: list_lines.iter().map(|(_, _, rank)| *rank).collect(),
+            cols: entry_cols,
+            rows: list_rows,
+        };
+
+        let mut body = vec![];
+        #[cfg(feature = "preview")]
+        let list_lines = match preview {
+            Some(preview) => {
+                self.push_preview(
+                    &mut body,
+                    list_lines,
+                    &preview,
+                    (list_rows, list_cols),
+                    (body_rows, cols),
+                );
+                vec![]
+            }
+            None => list_lines,
+        };
+        body.extend(list_lines.into_iter().map(|(line, _, _)| line));
+        if footer.is_some() || self.prompt_position == PromptPosition::Bottom
+        {
+            body.resize(body_rows.max(body.len()), String::new());
+        }
+
+        let prompt_line = self.prompt_line();
+        let mut lines = vec![];
+        match self.prompt_position {
+            PromptPosition::Top => {
+                lines.push(prompt_line);
+                lines.extend(status_line);
+                lines.extend(sort_header);
+                lines.extend(body);
+                lines.extend(footer);
+            }
+            PromptPosition::Bottom => {
+                lines.extend(footer);
+                lines.extend(body);
+                lines.extend(sort_header);
+                lines.extend(status_line);
+                lines.push(prompt_line);
+            }
+        }
+        screen.push_str(&lines.join("\n"));
+
+        self.needs_redraw = false;
+        self.headers = headers;
+        screen
+    }
+
+    fn prompt_line(&self) -> String {
+        let mut line = String::new();
+        if let Some(number_prompt) = &self.number_prompt {
+            write!(
+                line,
+                "  {} {}{}",
+                number_prompt.prompt,
+                number_prompt.input,
+                self.theme.cursor.paint(' ')
+            )
+            .unwrap();
+            if number_prompt.invalid {
+                write!(
+                    line,
+                    "{}",
+                    self.theme.hint.paint(format!(
+                        " ({})",
+                        self.message(Message::InvalidNumber {
+                            start: *number_prompt.range.start(),
+                            end: *number_prompt.range.end(),
+                        })
+                    ))
+                )
+                .unwrap();
+            }
+            return line;
+        }
+        if let Some(prompt) = &self.prompt {
+            line.push_str(&prompt(&self.view()));
+        } else {
+            line.push_str("  ");
+        }
+        if let InputMode::Custom(idx) = self.input_mode {
+            if let Some(mode) = &self.custom_modes[idx].1 {
+                line.push_str(&mode.prompt(self));
+            }
+        } else if self.input_mode == InputMode::Normal
+            && self.query.is_empty()
+        {
+            write!(line, "{}", self.theme.hint.paint(&self.placeholder.0))
+                .unwrap();
+        } else if self.query_selected {
+            write!(line, "{}", self.theme.cursor.paint(&self.query)).unwrap();
+        } else if self.input_mode == InputMode::Search && !self.unfocused {
+            let (before, after) = self.query.split_at(self.cursor);
+            let under_cursor = after.graphemes(true).next().unwrap_or(" ");
+            write!(
+                line,
+                "{}{}{}",
+                before,
+                self.theme.cursor.paint(under_cursor),
+                after.get(under_cursor.len()..).unwrap_or("")
+            )
+            .unwrap();
+        } else {
+            line.push_str(&self.query);
+        }
+        if self.recording.is_some() {
+            write!(
+                line,
+                "{}",
+                self.theme.recording.paint(format!(
+                    " ({})",
+                    self.message(Message::Recording)
+                ))
+            )
+            .unwrap();
+        }
+        if self.pending_search.is_some() || self.refreshing {
+            write!(
+                line,
+                "{}",
+                self.theme.hint.paint(format!(
+                    " ({})",
+                    self.message(Message::Searching)
+                ))
+            )
+            .unwrap();
+        }
+        if self.scroll_indicator == ScrollIndicator::Position
+            && !self.search_results.is_empty()
+        {
+            write!(
+                line,
+                "{}",
+                self.theme.hint.paint(format!(
+                    " ({})",
+                    self.message(Message::Position {
+                        selected: self.selected + 1,
+                        total: self.match_count,
+                    })
+                ))
+            )
+            .unwrap();
+        }
+        line
+    }
+
+    fn message(&self, message: Message) -> String {
+        self.message_formatter
+            .as_ref()
+            .and_then(|message_formatter| message_formatter(&message))
+            .unwrap_or_else(|| message.to_string())
+    }
+
+    fn status_line(&self) -> Option<String> {
+        if !self.status_line {
+            return None;
+        }
+
+        let mode = match self.input_mode {
+            InputMode::Normal => "normal",
+            InputMode::Search => "search",
+            InputMode::Custom(idx) => &self.custom_modes[idx].0,
+        };
+        let mut status = format!(
+            "  {} [{mode}]",
+            self.message(Message::Matches {
+                matched: self.match_count,
+                total: self.all_entries.len() - self.hidden.len(),
+            })
+        );
+        if self.multi_select && !self.marks.is_empty() {
+            write!(
+                status,
+                " ({})",
+                self.message(Message::Marked {
+                    count: self.marks.len()
+                })
+            )
+            .unwrap();
+        }
+        Some(format!("{}", self.theme.hint.paint(status)))
+    }
+
+    fn scroll_to(
+        &mut self,
+        first_row: usize,
+        selected_row: usize,
+        total_rows: usize,
+        visible_rows: usize,
+    ) -> usize {
+        let visible_rows = visible_rows.max(1);
+        let margin = self.scrolloff.min((visible_rows - 1) / 2);
+        if first_row < self.scroll_offset + margin {
+            self.scroll_offset = first_row.saturating_sub(margin);
+        }
+        if selected_row + margin >= self.scroll_offset + visible_rows {
+            self.scroll_offset = selected_row + margin + 1 - visible_rows;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(total_rows.saturating_sub(visible_rows));
+        self.scroll_offset
+    }
+
+    /// Returns the current search results, in sorted order. Together with
+    /// [`selected_result`](Self::selected_result) and
+    /// [`scroll_window`](Self::scroll_window), this allows plugins to draw
+    /// the list themselves instead of calling [`render`](Self::render).
+    pub fn results(
+        &self,
+    ) -> impl ExactSizeIterator<Item = SearchResultView<'_, T>> {
+        self.search_results
+            .iter()
+            .map(|search_result| self.result_view(search_result))
+    }
+
+    /// Returns the currently selected search result, if any entries match
+    /// the query. Its position in [`results`](Self::results) is
+    /// [`PickerView::selected`].
+    pub fn selected_result(&self) -> Option<SearchResultView<'_, T>> {
+        self.search_results
+            .get(self.selected)
+            .map(|search_result| self.result_view(search_result))
+    }
+
+    /// Returns the positions in [`results`](Self::results) which should be
+    /// shown in a list with the given number of rows, keeping the selected
+    /// result in view (along with the margin configured by
+    /// [`set_scrolloff`](Self::set_scrolloff)). Like
+    /// [`render`](Self::render), this updates the scroll position, so that
+    /// the list only scrolls when the selection would otherwise leave it.
+    pub fn scroll_window(&mut self, rows: usize) -> std::ops::Range<usize> {
+        let total = self.search_results.len();
+        let start = self.scroll_to(self.selected, self.selected, total, rows);
+        start..(start + rows).min(total)
+    }
+
+    fn result_view<'a>(
+        &'a self,
+        search_result: &'a SearchResult,
+    ) -> SearchResultView<'a, T> {
+        SearchResultView {
+            index: search_result.entry,
+            entry: &self.all_entries[search_result.entry],
+            score: search_result.score,
+            indices: &search_result.indices,
+        }
+    }
+
+    fn push_scrollbar(
+        &self,
+        out: &mut String,
+        width: usize,
+        cols: usize,
+        row: usize,
+        total_rows: usize,
+        visible_rows: usize,
+    ) {
+        let thumb_len = (visible_rows * visible_rows / total_rows).max(1);
+        let thumb_start = (self.scroll_offset * visible_rows / total_rows)
+            .min(visible_rows - thumb_len);
+        let c = if (thumb_start..thumb_start + thumb_len).contains(&row) {
+            self.glyph("█", "#")
+        } else {
+            self.glyph("│", "|")
+        };
+        write!(
+            out,
+            "{:pad$}{}",
+            "",
+            self.theme.hint.paint(c),
+            pad = cols.saturating_sub(width),
+        )
+        .unwrap();
+    }
+
+    fn glyph(
+        &self,
+        unicode: &'static str,
+        ascii: &'static str,
+    ) -> &'static str {
+        if self.ascii {
+            ascii
+        } else {
+            unicode
+        }
+    }
+
+    fn sort_header(&self, cols: usize) -> Option<String> {
+        if self.sort_columns.is_empty() {
+            return None;
+        }
+
+        let mut header = "  ".to_string();
+        let mut width = 2;
+        for (i, (name, _)) in self.sort_columns.iter().enumerate() {
+            let label = match self.sort {
+                Some((column, SortDirection::Ascending)) if column == i => {
+                    format!("{name} {}", self.glyph("▲", "^"))
+                }
+                Some((column, SortDirection::Descending)) if column == i => {
+                    format!("{name} {}", self.glyph("▼", "v"))
+                }
+                _ => name.clone(),
+            };
+            let label_width = str_width(&label, self.control_chars);
+            if width + label_width > cols {
+                break;
+            }
+            if self.sort.is_some_and(|(column, _)| column == i) {
+                write!(header, "{}", self.theme.heading.paint(label))
+                    .unwrap();
+            } else {
+                write!(header, "{}", self.theme.hint.paint(label)).unwrap();
+            }
+            header.push_str("  ");
+            width += label_width + 2;
+        }
+        Some(header)
+    }
+
+    #[cfg(feature = "preview")]
+    fn push_preview(
+        &self,
+        body: &mut Vec<String>,
+        list_lines: Vec<(String, usize, Option<usize>)>,
+        preview: &str,
+        (list_rows, list_cols): (usize, usize),
+        (body_rows, cols): (usize, usize),
+    ) {
+        let mut list_lines = list_lines.into_iter();
+        let mut preview_lines = preview.lines();
+        for _ in 0..list_rows {
+            let (mut line, width, _) = list_lines.next().unwrap_or_default();
+            if self.preview_position == PreviewPosition::Right {
+                write!(
+                    line,
+                    "{:pad$}{} ",
+                    "",
+                    self.theme.hint.paint(self.glyph("│", "|")),
+                    pad = list_cols.saturating_sub(width),
+                )
+                .unwrap();
+                push_preview_line(
+                    &mut line,
+                    preview_lines.next().unwrap_or(""),
+                    (cols - list_cols).saturating_sub(2),
+                );
+            }
+            body.push(line);
+        }
+        if self.preview_position == PreviewPosition::Bottom {
+            let separator = self.glyph("─", "-").repeat(cols);
+            body.push(format!("{}", self.theme.hint.paint(separator)));
+            for line in
+                preview_lines.take(body_rows.saturating_sub(list_rows + 1))
+            {
+                let mut out = String::new();
+                push_preview_line(&mut out, line, cols);
+                body.push(out);
+            }
+        }
+    }
+
+    fn footer(&self, cols: usize) -> Option<String> {
+        if self.esc_pressed_at.is_some() {
+            return Some(format!(
+                "{}",
+                self.theme
+                    .hint
+                    .paint(format!("({})", self.message(Message::EscAgain)))
+            ));
+        }
+        if self.permission_denied {
+            return Some(format!(
+                "{}",
+                self.theme.hint.paint(truncate_to_width(
+                    &format!("({})", self.message(Message::PermissionDenied)),
+                    cols
+                ))
+            ));
+        }
+
+        let tooltip = self.search_results.get(self.selected).and_then(
+            |search_result| {
+                self.all_entries[search_result.entry].tooltip.as_ref()
+            },
+        )?;
+        let mut line = String::new();
+        push_preview_line(
+            &mut line,
+            tooltip.lines().next().unwrap_or(""),
+            cols,
+        );
+        Some(format!("{}", self.theme.hint.paint(line)))
+    }
+
+    /// Declares that the picker can't work without the permissions your
+    /// plugin requested (for instance, because its entries come from
+    /// [`set_command_source`](Self::set_command_source) or from
+    /// [`Event::SessionUpdate`]), and subscribes to
+    /// [`EventType::PermissionRequestResult`]. If the user denies the
+    /// permission request, the picker shows a notice rather than silently
+    /// staying empty, and [`update`](Self::update) returns
+    /// [`Response::PermissionDenied`].
+    pub fn require_permissions(&mut self) {
+        if !self.requires_permissions {
+            subscribe(&[EventType::PermissionRequestResult]);
+        }
+        self.requires_permissions = true;
+    }
+
+    /// Returns true if the user denied the permissions the picker needs
+    /// (see [`require_permissions`](Self::require_permissions)).
+    pub fn permission_denied(&self) -> bool {
+        self.permission_denied
+    }
+
+    /// Returns the size of the pane the picker was most recently rendered
+    /// in, as `(rows, cols)`.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        self.size
+    }
+
+    /// Returns true if the picker needs to be redrawn. Your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function should return
+    /// true if this function returns true. The flag is cleared when the
+    /// picker is rendered, or by
+    /// [`take_needs_redraw`](Self::take_needs_redraw).
+    pub fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    /// Returns true if the picker needs to be redrawn, like
+    /// [`needs_redraw`](Self::needs_redraw), and clears the flag. This is
+    /// useful for plugins which don't always render the picker (for
+    /// instance, while showing a screen of their own), which would
+    /// otherwise keep asking zellij to redraw because the flag is only
+    /// cleared by rendering.
+    pub fn take_needs_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.needs_redraw)
+    }
+
+    /// Sets whether the picker has focus. Pickers without focus ignore key
+    /// and mouse events and don't draw the cursor in the query, so that a
+    /// plugin showing several pickers side by side (such as a file picker
+    /// and a command palette) can pass every event to all of them and only
+    /// change which one has focus. The default is true.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.unfocused = !focused;
+        self.needs_redraw = true;
+    }
+
+    /// Returns whether the picker has focus (see
+    /// [`set_focused`](Self::set_focused)).
+    pub fn is_focused(&self) -> bool {
+        !self.unfocused
+    }
+
+    /// Reads the picker options from configuration keys starting with
+    /// `<namespace>_` in [`load`](Self::load), so that each picker in a
+    /// plugin with several of them can be configured separately (see
+    /// [`PickerConfig::parse_namespaced`]).
+    pub fn set_config_namespace(&mut self, namespace: impl Into<String>) {
+        self.config_namespace = Some(namespace.into());
+    }
+
+    /// Returns the current list of entries in the picker.
+    pub fn entries(&self) -> &[Entry<T>] {
+        &self.all_entries
+    }
+
+    /// Selects the search result at the given rank, that is, the position
+    /// in the list as it is currently filtered and sorted (the same value
+    /// as [`Response::Select::rank`]), not the index into
+    /// [`entries`](Self::entries). Ranks past the end of the list select
+    /// the last result, and if there are no results, the selection is
+    /// reset to the top of the list. Returns the rank which was actually
+    /// selected.
+    ///
+    /// Changing the query re-runs the search, which moves the selection
+    /// back to the top (or keeps the same entry selected, see
+    /// [`set_keep_selection_on_search`](Self::set_keep_selection_on_search)),
+    /// so a rank is only meaningful until the next change to the query or
+    /// the list of entries. Use
+    /// [`select_entry`](Self::select_entry) to select a specific entry
+    /// instead.
+    pub fn select(&mut self, rank: usize) -> usize {
+        self.pending_selection = None;
+        self.selected = rank.min(self.search_results.len().saturating_sub(1));
+        self.needs_redraw = true;
+        self.report_selection_change();
+        self.selected
+    }
+
+    /// Selects the first search result.
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+
+    /// Selects the last search result which has been loaded so far (see
+    /// [`load_more`](Self::load_more)).
+    pub fn select_last(&mut self) {
+        self.select(usize::MAX);
+    }
+
+    /// Selects the entry at the given index into
+    /// [`entries`](Self::entries). Returns false (leaving the selection
+    /// unchanged) if that entry isn't part of the current search results,
+    /// for instance because it is hidden or doesn't match the query.
+    pub fn select_entry(&mut self, index: usize) -> bool {
+        let Some(rank) = self
+            .search_results
+            .iter()
+            .position(|search_result| search_result.entry == index)
+        else {
+            return false;
+        };
+        self.select(rank);
+        true
+    }
+
+    /// Removes all entries in the list.
+    ///
+    /// The selected entry is remembered (by its [key](Self::set_entry_key)),
+    /// and the next call to [`extend`](Self::extend) will select it again if
+    /// an entry with the same key is added. This means that the common
+    /// pattern of calling `clear` followed by `extend` whenever the
+    /// underlying data changes keeps the selection stable.
+    pub fn clear(&mut self) {
+        self.defer_after_update();
+        if self.batch.depth > 0 {
+            self.capture_batch_selection();
+        } else if self.pending_selection.is_none() {
+            self.pending_selection = self.selected_key();
+        }
+        if self.highlight_new.is_some() {
+            self.known_keys = self
+                .all_entries
+                .iter()
+                .map(|entry| self.entry_key(entry))
+                .collect();
+        }
+        self.all_entries.clear();
+        self.hidden.clear();
+        self.search();
+        self.selected = 0;
+    }
+
+    /// Sets the function used to identify entries across calls to
+    /// [`clear`](Self::clear) and [`extend`](Self::extend), so that the
+    /// selection can be preserved even when an entry's string changes. By
+    /// default, entries are identified by their string.
+    pub fn set_entry_key(
+        &mut self,
+        f: impl Fn(&Entry<T>) -> String + 'static,
+    ) {
+        self.entry_key = Some(Box::new(f));
+    }
+
+    /// Adds new entries to the list.
+    pub fn extend(&mut self, iter: impl IntoIterator<Item = Entry<T>>) {
+        let start = self.all_entries.len();
+        self.refreshing = false;
+        self.preserving_selection(|picker| picker.all_entries.extend(iter));
+        if self.highlight_new.is_some() {
+            self.mark_new_entries(start);
+        }
+    }
+
+    /// Adds new entries to the list, listed under a header with the given
+    /// name (see [`Entry::group`]).
+    pub fn extend_group(
+        &mut self,
+        group: &str,
+        iter: impl IntoIterator<Item = Entry<T>>,
+    ) {
+        self.extend(iter.into_iter().map(|entry| entry.with_group(group)));
+    }
+
+    /// Hides all entries for which the given function returns true. Hidden
+    /// entries remain in the list of [`entries`](Self::entries), but are
+    /// not matched or displayed until [`unhide_all`](Self::unhide_all) is
+    /// called. Entries that are already hidden stay hidden.
+    pub fn hide_where(&mut self, f: impl Fn(&Entry<T>) -> bool) {
+        self.preserving_selection(|picker| {
+            picker.hidden.extend(
+                picker
+                    .all_entries
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, entry)| f(entry).then_some(i)),
+            );
+        });
+    }
+
+    /// Removes all entries for which the given function returns false.
+    /// Unlike [`hide_where`](Self::hide_where), this removes the entries
+    /// from [`entries`](Self::entries) entirely, so the indices of the
+    /// remaining entries can change.
+    pub fn retain(&mut self, mut f: impl FnMut(&Entry<T>) -> bool) {
+        self.preserving_selection(|picker| {
+            let keep: Vec<_> =
+                picker.all_entries.iter().map(&mut f).collect();
+            if picker.highlight_new.is_some() {
+                // entries which are removed and added back later shouldn't
+                // count as new
+                for (entry, _) in picker
+                    .all_entries
+                    .iter()
+                    .zip(&keep)
+                    .filter(|(_, keep)| !**keep)
+                {
+                    picker.known_keys.insert(picker.entry_key(entry));
+                }
+            }
+            let hidden = std::mem::take(&mut picker.hidden);
+            picker.hidden = keep
+                .iter()
+                .enumerate()
+                .filter(|(_, keep)| **keep)
+                .enumerate()
+                .filter(|(_, (idx, _))| hidden.contains(idx))
+                .map(|(kept, _)| kept)
+                .collect();
+            let mut keep = keep.into_iter();
+            picker.all_entries.retain(|_| keep.next().unwrap());
+        });
+    }
+
+    /// Makes all entries hidden by [`hide_where`](Self::hide_where)
+    /// visible again.
+    pub fn unhide_all(&mut self) {
+        self.preserving_selection(|picker| picker.hidden.clear());
+    }
+
+    /// Returns true if the entry at the given index in
+    /// [`entries`](Self::entries) is currently hidden.
+    pub fn is_hidden(&self, idx: usize) -> bool {
+        self.hidden.contains(&idx)
+    }
+
+    /// Registers a function to be called with the currently selected entry
+    /// just before a different entry becomes selected. This can be used to
+    /// cancel any in-flight work (such as preview commands) associated with
+    /// the entry that is losing the selection.
+    pub fn on_before_selection_change(
+        &mut self,
+        f: impl FnMut(&Entry<T>) + 'static,
+    ) {
+        self.before_selection_change = Some(Box::new(f));
+    }
+
+    /// Registers a function to be called whenever a different entry
+    /// becomes selected, whether through keyboard navigation, searching, or
+    /// changes to the list of entries. The neighboring search results are
+    /// provided as well, so that work for them can be started ahead of time.
+    pub fn on_after_selection_change(
+        &mut self,
+        f: impl FnMut(SelectionChange<'_, T>) + 'static,
+    ) {
+        self.after_selection_change = Some(Box::new(f));
+    }
+
+    /// Request that the fuzzy matcher always respect case when matching.
+    pub fn use_case_matching_respect(&mut self) {
+        self.case_matching = nucleo_matcher::pattern::CaseMatching::Respect;
+    }
+
+    /// Request that the fuzzy matcher always ignore case when matching.
+    pub fn use_case_matching_ignore(&mut self) {
+        self.case_matching = nucleo_matcher::pattern::CaseMatching::Ignore;
+    }
+
+    /// Request that the fuzzy matcher respect case when matching if the
+    /// query contains any uppercase characters, but ignore case otherwise.
+    /// This is the default.
+    pub fn use_case_matching_smart(&mut self) {
+        self.case_matching = nucleo_matcher::pattern::CaseMatching::Smart;
+    }
+
+    /// Puts the picker into search mode (equivalent to pressing `/` when in
+    /// normal mode).
+    pub fn enter_search_mode(&mut self) {
+        self.input_mode = InputMode::Search;
+    }
+
+    /// Puts the picker into normal mode (equivalent to pressing Escape when
+    /// in search mode). This is the default.
+    pub fn enter_normal_mode(&mut self) {
+        self.input_mode = InputMode::Normal;
+    }
+
+    /// Enables multi-select mode. In this mode, pressing Tab (or the key set
+    /// with [`set_mark_key`](Self::set_mark_key)) marks or unmarks the
+    /// highlighted entry, and Enter returns [`Response::SelectMulti`] with
+    /// all of the marked entries.
+    pub fn enable_multi_select(&mut self) {
+        self.multi_select = true;
+        self.needs_redraw = true;
+    }
+
+    /// Disables multi-select mode. This is the default.
+    pub fn disable_multi_select(&mut self) {
+        self.multi_select = false;
+        self.marks.clear();
+        self.needs_redraw = true;
+    }
+
+    /// Registers a key which closes the picker with
+    /// [`Response::Action`], reporting the given name along with the
+    /// highlighted entry. This can be used to provide additional operations
+    /// on entries, such as deleting them or opening them in a different
+    /// way. Registered keys take precedence over both the built in keys and
+    /// those bound with [`bind`](Self::bind), and unmodified character keys
+    /// are only registered in normal mode.
+    pub fn register_action(
+        &mut self,
+        name: impl Into<String>,
+        key: KeyWithModifier,
+    ) {
+        self.action_keys
+            .retain(|(registered, _)| *registered != key);
+        self.action_keys.push((key, name.into()));
+    }
+
+    /// Enables mouse support. Clicking on an entry selects it, and
+    /// double-clicking chooses it (see
+    /// [`set_mouse_select`](Self::set_mouse_select)). The scroll wheel
+    /// moves the selection. In multi-select mode, dragging across entries
+    /// marks all of them, and right-clicking an entry marks or unmarks it.
+    /// (Zellij doesn't report modifier keys for mouse events, so
+    /// shift-click and ctrl-click can't be distinguished from a plain
+    /// click.)
+    #[cfg(feature = "mouse")]
+    pub fn enable_mouse(&mut self) {
+        if !self.mouse {
+            subscribe(&[EventType::Mouse]);
+        }
+        self.mouse = true;
+    }
+
+    /// Configures how many clicks it takes to choose an entry.
+    #[cfg(feature = "mouse")]
+    pub fn set_mouse_select(&mut self, mouse_select: MouseSelect) {
+        self.mouse_select = mouse_select;
+    }
+
+    /// Disables mouse support. This is the default.
+    #[cfg(feature = "mouse")]
+    pub fn disable_mouse(&mut self) {
+        if self.mouse {
+            unsubscribe(&[EventType::Mouse]);
+        }
+        self.mouse = false;
+        self.drag = None;
+    }
+
+    /// Accepts entries sent through the pipe with the given name (for
+    /// instance with `zellij pipe --name <name>`), when passed to
+    /// [`handle_pipe`](Picker::handle_pipe). This can also be set through
+    /// the `nucleo_pipe_name` configuration option. Your plugin will need
+    /// the [`ReadCliPipes`](PermissionType::ReadCliPipes) permission to
+    /// receive messages from the command line.
+    pub fn set_pipe_name(&mut self, name: impl Into<String>) {
+        self.pipe_name = Some(name.into());
+    }
+
+    /// Stops accepting entries through a pipe. This is the default.
+    pub fn clear_pipe_name(&mut self) {
+        self.pipe_name = None;
+        if let Some(pipe_id) = self.pipe_output.take() {
+            unblock_cli_pipe_input(&pipe_id);
+        }
+    }
+
+    /// Sets the key used to mark entries in multi-select mode. The default
+    /// is Tab.
+    pub fn set_mark_key(&mut self, key: KeyWithModifier) {
+        self.mark_key = Some(key);
+    }
+
+    /// Binds a key to an action. Bindings added this way take precedence
+    /// over the built in keys. Unmodified character keys are only bound in
+    /// normal mode, so that they can still be typed into the query in
+    /// search mode.
+    ///
+    /// Bindings can also be set through the configuration, by setting
+    /// `nucleo_keybind_<action>` (for instance, `nucleo_keybind_down`) to a
+    /// comma separated list of keys, such as `"Ctrl n, Ctrl j"`.
+    pub fn bind(&mut self, key: KeyWithModifier, action: Action) {
+        self.keybindings.retain(|(bound, _)| *bound != key);
+        self.keybindings.push((key, Some(action)));
+    }
+
+    /// Replaces the bindings from the current preset with the bindings
+    /// from the given one. Bindings added with [`bind`](Self::bind) and
+    /// [`unbind`](Self::unbind) (or the `nucleo_keybind_<action>`
+    /// options) are kept, and still take precedence over the preset. This
+    /// can also be set with the `nucleo_keymap` configuration option.
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    /// Removes the binding for a key, including any built in binding.
+    pub fn unbind(&mut self, key: KeyWithModifier) {
+        self.keybindings.retain(|(bound, _)| *bound != key);
+        self.keybindings.push((key, None));
+    }
+
+    /// Returns the indices in [`entries`](Self::entries) of the entries
+    /// that are currently marked, in the order they were marked.
+    pub fn marked(&self) -> Vec<usize> {
+        self.marks
+            .iter()
+            .filter_map(|mark| {
+                self.all_entries
+                    .iter()
+                    .position(|entry| self.entry_key(entry) == *mark)
+            })
+            .collect()
+    }
+
+    /// Unmarks all entries.
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+        self.needs_redraw = true;
+    }
+
+    /// Registers an additional input mode under the given name, which can
+    /// then be activated with [`enter_mode`](Self::enter_mode).
+    pub fn add_mode(
+        &mut self,
+        name: impl Into<String>,
+        mode: impl CustomMode<T> + 'static,
+    ) {
+        self.custom_modes.push((name.into(), Some(Box::new(mode))));
+    }
+
+    /// Switches to the custom input mode registered with the given name.
+    /// Returns false if no such mode was registered.
+    pub fn enter_mode(&mut self, name: &str) -> bool {
+        let Some(idx) = self
+            .custom_modes
+            .iter()
+            .position(|(mode_name, _)| mode_name == name)
+        else {
+            return false;
+        };
+        self.input_mode = InputMode::Custom(idx);
+        self.needs_redraw = true;
+        true
+    }
+
+    /// Asks a follow-up question about the entry at the given index in
+    /// [`entries`](Self::entries), such as how many lines of a file to
+    /// show after the file was chosen. The prompt replaces the query line
+    /// until the user enters a number in `range` and presses Enter, at which
+    /// point [`update`](Self::update) returns [`Response::Number`]. Numbers
+    /// outside of the range aren't accepted, and pressing Escape abandons
+    /// the prompt and goes back to the list.
+    pub fn prompt_number(
+        &mut self,
+        index: usize,
+        prompt: impl Into<String>,
+        range: std::ops::RangeInclusive<i64>,
+    ) {
+        self.number_prompt = Some(NumberPrompt {
+            index,
+            prompt: prompt.into(),
+            range,
+            input: String::new(),
+            invalid: false,
+        });
+        self.needs_redraw = true;
+    }
+
+    /// Returns true while waiting for an answer to
+    /// [`prompt_number`](Self::prompt_number).
+    pub fn is_prompting(&self) -> bool {
+        self.number_prompt.is_some()
+    }
+
+    /// Returns the name of the active custom input mode, if any.
+    pub fn current_mode(&self) -> Option<&str> {
+        if let InputMode::Custom(idx) = self.input_mode {
+            Some(&self.custom_modes[idx].0)
+        } else {
+            None
+        }
+    }
+
+    /// When enabled, pressing `q` in normal mode starts or stops recording
+    /// a macro of the keys pressed in the picker, and pressing `@` replays
+    /// it. This is disabled by default, since it takes over those keys
+    /// (macros can still be used through
+    /// [`start_recording`](Self::start_recording) and
+    /// [`replay_macro`](Self::replay_macro) while it's disabled).
+    pub fn set_macros(&mut self, macros: bool) {
+        self.macros = macros;
+    }
+
+    /// Starts recording a macro of the keys pressed in the picker
+    /// (equivalent to pressing `q` in normal mode, see
+    /// [`set_macros`](Self::set_macros)).
+    pub fn start_recording(&mut self) {
+        self.recording = Some(vec![]);
+        self.needs_redraw = true;
+    }
+
+    /// Stops recording a macro (equivalent to pressing `q` in normal mode
+    /// while recording). The recorded keys replace the current macro.
+    pub fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            self.recorded_macro = recording;
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Returns the keys in the current macro.
+    pub fn recorded_macro(&self) -> &[KeyWithModifier] {
+        &self.recorded_macro
+    }
+
+    /// Replaces the current macro, for instance to share a macro between
+    /// several pickers.
+    pub fn set_recorded_macro(&mut self, keys: Vec<KeyWithModifier>) {
+        self.recorded_macro = keys;
+    }
+
+    /// Replays the keys in the current macro (equivalent to pressing `@`
+    /// in normal mode). If any of the keys results in a [`Response`],
+    /// replaying stops and that response is returned.
+    pub fn replay_macro(&mut self) -> Option<Response> {
+        let keys = self.recorded_macro.clone();
+        let response = keys.iter().find_map(|key| self.handle_key(key));
+        self.report_selection_change();
+        response
+    }
+
+    /// Sets a function used to generate the text displayed before the query
+    /// at the start of the prompt line. It is called on every render, so it
+    /// can display things like the current number of matches.
+    pub fn set_prompt(
+        &mut self,
+        f: impl Fn(&PickerView<'_>) -> String + 'static,
+    ) {
+        self.prompt = Some(Box::new(f));
+        self.needs_redraw = true;
+    }
+
+    /// Sets a function used to generate preview text for the selected
+    /// entry, which is displayed next to (or below) the list of entries. It
+    /// is called on every render, so any expensive work should be cached by
+    /// the caller. This is only available with the `preview` feature.
+    #[cfg(feature = "preview")]
+    pub fn set_preview(&mut self, f: impl Fn(&Entry<T>) -> String + 'static) {
+        self.preview = Some(Box::new(f));
+        self.needs_redraw = true;
+    }
+
+    /// Removes the preview function, if one was set.
+    #[cfg(feature = "preview")]
+    pub fn clear_preview(&mut self) {
+        self.preview = None;
+        self.needs_redraw = true;
+    }
+
+    /// Sets a function used to render each entry in the list, in place of
+    /// the default rendering. It is called with the columns left after the
+    /// selection and mark indicators, and should return a single line
+    /// (which may include ANSI escape sequences) no wider than that.
+    pub fn set_row_renderer(
+        &mut self,
+        f: impl Fn(&RowContext<'_, T>) -> String + 'static,
+    ) {
+        self.row_renderer = Some(Box::new(f));
+        self.needs_redraw = true;
+    }
+
+    /// Removes the row rendering function, if one was set.
+    pub fn clear_row_renderer(&mut self) {
+        self.row_renderer = None;
+        self.needs_redraw = true;
+    }
+
+    /// Configures where the preview area is displayed.
+    #[cfg(feature = "preview")]
+    pub fn set_preview_position(&mut self, position: PreviewPosition) {
+        self.preview_position = position;
+        self.needs_redraw = true;
+    }
+
+    /// Configures whether the prompt line is displayed at the top or the
+    /// bottom of the pane.
+    pub fn set_prompt_position(&mut self, position: PromptPosition) {
+        self.prompt_position = position;
+        self.needs_redraw = true;
+    }
+
+    /// Configures whether the best match is displayed at the top or the
+    /// bottom of the list.
+    pub fn set_list_direction(&mut self, direction: ListDirection) {
+        self.list_direction = direction;
+        self.needs_redraw = true;
+    }
+
+    /// Sets the text displayed in the prompt line while the query is empty
+    /// in normal mode. The default is `(press / to search)`.
+    pub fn set_placeholder(&mut self, placeholder: impl Into<String>) {
+        self.placeholder = Placeholder(placeholder.into());
+        self.needs_redraw = true;
+    }
+
+    /// Sets a function used to produce the text of the messages the picker
+    /// displays (such as the number of marked entries), for translating
+    /// them or adjusting their wording. The function receives the message
+    /// along with any counts it includes, so the text can use the right
+    /// plural forms (see [`plural`]). Returning `None` uses the default
+    /// English text.
+    pub fn set_message_formatter(
+        &mut self,
+        f: impl Fn(&Message) -> Option<String> + 'static,
+    ) {
+        self.message_formatter = Some(Box::new(f));
+        self.needs_redraw = true;
+    }
+
+    /// When enabled, a line showing the number of matching entries and the
+    /// current input mode is displayed next to the prompt line.
+    pub fn set_status_line(&mut self, status_line: bool) {
+        self.status_line = status_line;
+        self.needs_redraw = true;
+    }
+
+    /// Returns a summary of the current state of the picker.
+    pub fn view(&self) -> PickerView<'_> {
+        PickerView {
+            query: &self.query,
+            searching: self.input_mode == InputMode::Search,
+            mode: self.current_mode(),
+            match_count: self.match_count,
+            entry_count: self.all_entries.len(),
+            selected: self.selected,
+            marked_count: self.marks.len(),
+        }
+    }
+
+    /// Returns the current search query.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replaces the search query, and moves the cursor to the end of it.
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.defer_after_update();
+        self.query = query.into();
+        self.cursor = self.query.len();
+        self.query_selected = false;
+        self.query_changed();
+    }
+
+    /// When enabled, the query which was last submitted (by selecting an
+    /// entry or with [`Response::Query`]) is restored the next time the
+    /// picker is [opened](Self::open), so that the previous search can be
+    /// repeated or refined. The restored query is selected, so typing
+    /// replaces it and Backspace clears it, while any other key keeps it.
+    /// When this is enabled through the plugin configuration, the picker
+    /// is opened automatically when zellij shows the plugin again after a
+    /// response was returned. The default is false.
+    pub fn set_remember_query(&mut self, remember_query: bool) {
+        self.remember_query = remember_query;
+        if !remember_query {
+            self.remembered_query = None;
+        }
+    }
+
+    /// Resets the query for a new invocation of the picker, after it was
+    /// hidden once it returned a response. The query is cleared, or
+    /// restored if [`set_remember_query`](Self::set_remember_query) is
+    /// enabled and
+    /// [`skip_remembered_query`](Self::skip_remembered_query) wasn't
+    /// called.
+    pub fn open(&mut self) {
+        self.closed = false;
+        let query = if std::mem::take(&mut self.skip_remembered_query) {
+            String::new()
+        } else {
+            self.remembered_query.clone().unwrap_or_default()
+        };
+        self.set_query(query);
+        self.query_selected = !self.query.is_empty();
+    }
+
+    /// Makes the next call to [`open`](Self::open) start with an empty
+    /// query, even if [`set_remember_query`](Self::set_remember_query) is
+    /// enabled. The remembered query is kept for later invocations.
+    pub fn skip_remembered_query(&mut self) {
+        self.skip_remembered_query = true;
+    }
+
+    /// Runs `f`, deferring the searches which would normally happen after
+    /// each change to the list of entries or the query until it returns,
+    /// so that they only happen once. This is useful when replacing the
+    /// entries with [`clear`](Self::clear) and [`extend`](Self::extend)
+    /// outside of your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function (changes made
+    /// after calling [`update`](Self::update) are batched automatically).
+    ///
+    /// While the batch is running, the search results are empty (since
+    /// they may refer to entries which were removed), and selection change
+    /// callbacks aren't called. Once it finishes, the previously selected
+    /// entry is selected again if it is still in the results (or, if the
+    /// list was left empty, when it is added again), and otherwise the
+    /// first entry is selected.
+    ///
+    /// Keys passed to a [`CustomMode`] are handled as a batch, so custom
+    /// modes can safely modify the entries while handling a key.
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.batch.depth += 1;
+        let ret = f(self);
+        self.batch.depth -= 1;
+        if self.batch.depth == 0 {
+            self.finish_batch();
+        }
+        ret
+    }
+
+    /// Configures the fuzzy matcher to adjust matching bonuses appropriate
+    /// for matching paths.
+    pub fn set_match_paths(&mut self) {
+        self.match_paths = true;
+        self.matcher.config.set_match_paths();
+    }
+
+    /// Configures the fuzzy matcher to adjust matching bonuses appropriate
+    /// for matching arbitrary strings. This is the default.
+    pub fn clear_match_paths(&mut self) {
+        self.match_paths = false;
+        let prefer_prefix = self.matcher.config.prefer_prefix;
+        self.matcher.config = nucleo_matcher::Config::DEFAULT;
+        self.matcher.config.prefer_prefix = prefer_prefix;
+    }
+
+    /// Configures the fuzzy matcher to give a bonus to matches which are
+    /// closer to the start of an entry. This is a small adjustment which
+    /// only breaks ties between otherwise similar matches; see
+    /// [`set_prefix_first`](Self::set_prefix_first) for a stronger
+    /// version. The default is false.
+    pub fn set_prefer_prefix(&mut self, prefer_prefix: bool) {
+        self.matcher.config.prefer_prefix = prefer_prefix;
+        self.search();
+    }
+
+    /// When enabled, entries which start with the query are always listed
+    /// before entries which only match it somewhere else, regardless of
+    /// their scores (so that `re` lists `rename` before `prepare`, as
+    /// command palettes usually want). Case is ignored when comparing, and
+    /// leading and trailing whitespace in the query is ignored. The
+    /// default is false.
+    pub fn set_prefix_first(&mut self, prefix_first: bool) {
+        self.prefix_first = prefix_first;
+        self.search();
+    }
+
+    /// When enabled, matches whose matched characters all have the same
+    /// case as the query are listed before matches which only match when
+    /// ignoring case (so that `FB` lists `FooBar` before `foobar`), while
+    /// keeping the order by score within each of those. Unlike switching
+    /// to case sensitive matching, this doesn't change which entries
+    /// match, and toggling it (with [`Action::TogglePreferExactCase`],
+    /// Alt-c by default) only reorders the current results rather than
+    /// searching again. The default is false.
+    pub fn set_prefer_exact_case(&mut self, prefer_exact_case: bool) {
+        self.prefer_exact_case = prefer_exact_case;
+        self.rerank();
+    }
+
+    /// Returns whether matches with the same case as the query are listed
+    /// first (see [`set_prefer_exact_case`](Self::set_prefer_exact_case)).
+    pub fn prefer_exact_case(&self) -> bool {
+        self.prefer_exact_case
+    }
+
+    /// Configures what happens when Enter is pressed while no entries match
+    /// the current query.
+    pub fn set_empty_select(&mut self, empty_select: EmptySelect) {
+        self.empty_select = empty_select;
+    }
+
+    /// Configures how entries marked as [`current`](Entry::current) are
+    /// presented.
+    pub fn set_current_entry(&mut self, current_entry: CurrentEntry) {
+        self.current_entry = current_entry;
+        self.search();
+    }
+
+    /// Configures whether pressing a digit in normal mode chooses the entry
+    /// at that position. Disabling this avoids accidentally choosing an
+    /// entry when trying to search for a number, for instance in lists of
+    /// numbered items.
+    pub fn set_digit_shortcuts(&mut self, digit_shortcuts: DigitShortcuts) {
+        self.digit_shortcuts = digit_shortcuts;
+    }
+
+    /// Configures whether only the matched characters of an entry are
+    /// highlighted, or the whole words containing them. Highlighting whole
+    /// words can be easier to scan in lists of natural-language entries,
+    /// where individual matched characters tend to be scattered.
+    pub fn set_match_highlight(&mut self, match_highlight: MatchHighlight) {
+        self.match_highlight = match_highlight;
+        self.needs_redraw = true;
+    }
+
+    /// When set, the results are divided into strong, medium and weak
+    /// matches by their score, with a separator drawn wherever the band
+    /// changes. This gives an idea of when the remaining results are
+    /// unlikely to be useful. Nothing is drawn while the query is empty,
+    /// since every entry matches equally well then.
+    pub fn set_score_bands(&mut self, score_bands: Option<ScoreBands>) {
+        self.score_bands = score_bands;
+        self.update_headers();
+        self.needs_redraw = true;
+    }
+
+    /// Configures how control characters in entries are rendered.
+    pub fn set_control_chars(&mut self, control_chars: ControlChars) {
+        self.control_chars = control_chars;
+        self.needs_redraw = true;
+    }
+
+    /// When enabled, the picker only uses ASCII characters for the parts
+    /// it draws itself (such as markers, separators and the scrollbar), for
+    /// terminals or fonts which render other characters poorly. The text
+    /// of entries is still displayed as is.
+    pub fn set_ascii(&mut self, ascii: bool) {
+        self.ascii = ascii;
+        self.needs_redraw = true;
+    }
+
+    /// Sets the minimum number of entries to keep visible above and below
+    /// the selected entry when scrolling. The default is 0.
+    pub fn set_scrolloff(&mut self, scrolloff: usize) {
+        self.scrolloff = scrolloff;
+        self.needs_redraw = true;
+    }
+
+    /// Sets how the scroll position of the list is indicated.
+    pub fn set_scroll_indicator(
+        &mut self,
+        scroll_indicator: ScrollIndicator,
+    ) {
+        self.scroll_indicator = scroll_indicator;
+        self.needs_redraw = true;
+    }
+
+    /// Sets the colors used to draw the picker. Besides the default theme,
+    /// there are presets for users who have trouble reading it (see
+    /// [`Theme::high_contrast`], [`Theme::deuteranopia`] and
+    /// [`Theme::monochrome`]). The theme is replaced when following the
+    /// zellij theme (see
+    /// [`set_follow_zellij_theme`](Self::set_follow_zellij_theme)).
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.needs_redraw = true;
+    }
+
+    /// Returns the colors used to draw the picker.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Draws a marker in the given style next to each entry from the given
+    /// [`source`](Entry::source), so that users can tell at a glance where
+    /// the entries in a merged picker came from (for instance, blue for
+    /// tabs and green for sessions). Once any source has a style, a column
+    /// is reserved for the marker in front of every entry, and entries from
+    /// sources without a style leave it blank.
+    pub fn set_source_style(
+        &mut self,
+        source: impl Into<String>,
+        style: TextStyle,
+    ) {
+        self.source_styles.insert(source.into(), style);
+        self.needs_redraw = true;
+    }
+
+    /// Removes all styles set by
+    /// [`set_source_style`](Self::set_source_style).
+    pub fn clear_source_styles(&mut self) {
+        self.source_styles.clear();
+        self.needs_redraw = true;
+    }
+
+    /// Lists entries from the given [`source`](Entry::source) above entries
+    /// from sources with a lower priority, regardless of their scores (so
+    /// that sessions can always be listed above files, for instance).
+    /// Entries are still ordered by score within each source, and entries
+    /// without a source or from sources without a priority have a
+    /// priority of 0. Groups and pinned entries still come first.
+    pub fn set_source_priority(
+        &mut self,
+        source: impl Into<String>,
+        priority: i32,
+    ) {
+        self.source_priorities.insert(source.into(), priority);
+        self.search();
+    }
+
+    /// Limits the number of search results from the given
+    /// [`source`](Entry::source) to the best `limit` matches, so that a
+    /// source with many entries doesn't drown out the others in a merged
+    /// picker. `None` removes the limit.
+    pub fn set_source_limit(
+        &mut self,
+        source: impl Into<String>,
+        limit: Option<usize>,
+    ) {
+        let source = source.into();
+        if let Some(limit) = limit {
+            self.source_limits.insert(source, limit);
+        } else {
+            self.source_limits.remove(&source);
+        }
+        self.search();
+    }
+
+    /// When enabled, the theme is updated to match the colors of the
+    /// active zellij theme whenever zellij sends a
+    /// [`ModeUpdate`](Event::ModeUpdate) event (see
+    /// [`Theme::from_palette`]). This requires the
+    /// [`ReadApplicationState`](PermissionType::ReadApplicationState)
+    /// permission.
+    pub fn set_follow_zellij_theme(&mut self, follow: bool) {
+        if follow && !self.follow_zellij_theme {
+            subscribe(&[EventType::ModeUpdate]);
+        }
+        self.follow_zellij_theme = follow;
+    }
+
+    /// When enabled, the first press of Escape leaves search mode (or
+    /// clears the query, in normal mode), and pressing it again shortly
+    /// afterwards cancels the picker. A reminder is shown at the bottom of
+    /// the picker while the second press is pending.
+    pub fn set_double_esc_cancel(&mut self, double_esc_cancel: bool) {
+        if double_esc_cancel && !self.double_esc_cancel {
+            subscribe(&[EventType::Timer]);
+        }
+        self.double_esc_cancel = double_esc_cancel;
+        self.esc_pressed_at = None;
+    }
+
+    /// When enabled, moving the selection while in search mode returns to
+    /// normal mode, so that the normal mode keys can be used right away.
+    pub fn set_exit_search_on_navigation(&mut self, exit: bool) {
+        self.exit_search_on_navigation = exit;
+    }
+
+    /// When enabled, editing the query keeps the selected entry (and so the
+    /// visible page of results) as long as it still matches, rather than
+    /// always selecting the first result.
+    pub fn set_keep_selection_on_search(&mut self, keep: bool) {
+        self.keep_selection_on_search = keep;
+    }
+
+    /// When enabled, the selected entry stays on the same row of the
+    /// screen when entries are added or removed, with the list scrolling
+    /// around it instead. Without this, entries which are streamed in and
+    /// sorted above the selected entry push it further down the list
+    /// while it is being looked at. The default is false.
+    pub fn set_anchor_selection(&mut self, anchor: bool) {
+        self.anchor_selection = anchor;
+    }
+
+    /// When set, entries which weren't in the list before the most recent
+    /// call to [`clear`](Self::clear) (or which were added with
+    /// [`extend`](Self::extend) without clearing the list first) are
+    /// highlighted for the given amount of time, using
+    /// [`Theme::new_entry`]. This makes changes easier to notice in lists
+    /// which are refreshed from a live source. Entries are compared by
+    /// their [key](Self::set_entry_key), and the first entries added to an
+    /// empty picker aren't highlighted.
+    pub fn set_highlight_new(
+        &mut self,
+        duration: Option<std::time::Duration>,
+    ) {
+        if duration.is_some() && self.highlight_new.is_none() {
+            subscribe(&[EventType::Timer]);
+        }
+        self.highlight_new = duration;
+        if duration.is_none() {
+            self.known_keys.clear();
+            self.new_entries.clear();
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Sets the marker displayed in place of the parts of an entry that
+    /// don't fit in the pane. The default is `[...]`.
+    pub fn set_truncation_marker(&mut self, marker: impl Into<String>) {
+        self.truncation_marker = TruncationMarker(marker.into());
+        self.needs_redraw = true;
+    }
+
+    /// Returns the number of columns reserved at the end of a line for the
+    /// truncation marker when an entry doesn't fit in the pane (the width
+    /// of the marker plus a separating space).
+    pub fn truncation_margin(&self) -> usize {
+        display_width(&self.truncation_marker.0) + 1
+    }
+
+    /// When enabled, entries that are too long to fit are scrolled
+    /// horizontally so that the first matched character is always visible,
+    /// with the hidden start of the entry replaced by a marker.
+    pub fn set_keep_match_visible(&mut self, keep_match_visible: bool) {
+        self.keep_match_visible = keep_match_visible;
+        self.needs_redraw = true;
+    }
+
+    /// When set, searches triggered by editing the query only match this
+    /// many entries at a time, and continue matching the rest on later
+    /// calls to [`update`](Self::update) (driven by a timer) so that typing
+    /// stays responsive with very large lists of entries. The previous
+    /// results stay visible until the search finishes. The default is
+    /// `None`, which matches all entries at once.
+    ///
+    /// Independently of this setting, typing more characters at the end of
+    /// the query only matches the entries from the previous results again.
+    pub fn set_search_chunk_size(&mut self, chunk_size: Option<usize>) {
+        if chunk_size.is_some() && self.search_chunk_size.is_none() {
+            subscribe(&[EventType::Timer]);
+        }
+        self.search_chunk_size =
+            chunk_size.map(|chunk_size| chunk_size.max(1));
+        if chunk_size.is_none() {
+            self.finish_pending_search();
+        }
+    }
+
+    /// Returns true if a search is still in progress (see
+    /// [`set_search_chunk_size`](Self::set_search_chunk_size)), or hasn't
+    /// been started yet (see
+    /// [`set_coalesce_input`](Self::set_coalesce_input)), or if the
+    /// entries are being reloaded (see [`Response::Refresh`]).
+    pub fn is_searching(&self) -> bool {
+        self.pending_search.is_some()
+            || self.query_edited
+            || self.refreshing
+            || self
+                .command_source
+                .as_ref()
+                .is_some_and(CommandSource::is_running)
+    }
+
+    /// When enabled, typing into the query doesn't search immediately.
+    /// Instead, the search is deferred until something needs the results:
+    /// the next call to [`render`](Self::render), a key which isn't just
+    /// editing the query (such as moving the selection or pressing Enter),
+    /// any other event, or an explicit call to [`flush`](Self::flush). This
+    /// means that a burst of keys (from a fast typist or key repeat)
+    /// delivered before the next render only searches once, rather than
+    /// once per key. The default is false.
+    ///
+    /// While a search is deferred, [`results`](Self::results) and related
+    /// functions still return the results of the previous query.
+    pub fn set_coalesce_input(&mut self, coalesce: bool) {
+        self.coalesce_input = coalesce;
+        if !coalesce {
+            self.flush();
+        }
+    }
+
+    /// Runs the search for the current query, if it was deferred by
+    /// [`set_coalesce_input`](Self::set_coalesce_input) or by changing the
+    /// entries after a call to [`update`](Self::update). This does nothing
+    /// if the results are already up to date.
+    pub fn flush(&mut self) {
+        self.finish_update_batch();
+        if std::mem::take(&mut self.query_edited) {
+            self.query_changed();
+        }
+    }
+
+    /// Limits the number of search results that are kept sorted at once.
+    /// Additional results can be loaded with
+    /// [`load_more`](Self::load_more), or by moving the selection past the
+    /// end of the list. This keeps searching fast for very large lists of
+    /// entries, since only the best results need to be sorted.
+    pub fn set_result_limit(&mut self, limit: Option<usize>) {
+        self.result_limit = limit.map(|limit| limit.max(1));
+        self.result_pages = 0;
+        self.search();
+    }
+
+    /// When set, lists taller than this many rows are rendered in two
+    /// steps: the first `rows` rows of the list (along with the selected
+    /// entry) are rendered immediately, and the remaining rows are left
+    /// blank until the next call to [`update`](Self::update) (driven by a
+    /// timer) fills them in. Rows which were already rendered in the
+    /// previous frame are reused rather than left blank. This keeps the
+    /// picker responsive in very tall panes when rendering each entry is
+    /// expensive (for instance, with a
+    /// [row renderer](Self::set_row_renderer) which aligns columns). The
+    /// default is `None`, which renders every row at once.
+    pub fn set_render_slice(&mut self, rows: Option<usize>) {
+        if rows.is_some() && self.render_slice.is_none() {
+            subscribe(&[EventType::Timer]);
+        }
+        self.render_slice = rows.map(|rows| rows.max(1));
+        self.needs_redraw = true;
+    }
+
+    /// Loads another batch of search results, if the number of results was
+    /// limited with [`set_result_limit`](Self::set_result_limit) and there
+    /// are more matching entries than were loaded. Returns true if any new
+    /// results were loaded.
+    pub fn load_more(&mut self) -> bool {
+        if self.search_results.len() >= self.match_count {
+            return false;
+        }
+        self.result_pages += 1;
+        self.search();
+        true
+    }
+
+    /// Returns statistics about the work done by the picker, for instance
+    /// to display how long searching took.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns information about how the user arrived at the most recent
+    /// [`Response`] returned by [`update`](Self::update), such as how long
+    /// it took and the final query, or `None` if there hasn't been a
+    /// response yet. This is only available with the `telemetry` feature.
+    #[cfg(feature = "telemetry")]
+    pub fn telemetry(&self) -> Option<&Telemetry> {
+        self.telemetry.as_ref()
+    }
+
+    /// Returns the total number of entries matching the current query,
+    /// including any that haven't been loaded yet due to
+    /// [`set_result_limit`](Self::set_result_limit).
+    pub fn match_count(&self) -> usize {
+        self.match_count
+    }
+
+    /// When enabled, entries only need to match one of the
+    /// whitespace-separated terms in the query rather than all of them.
+    /// Entries are scored by the best matching term.
+    pub fn set_match_any_term(&mut self, match_any_term: bool) {
+        self.match_any_term = match_any_term;
+        self.search();
+    }
+
+    /// Registers a function which adjusts the score of each matching entry
+    /// after fuzzy matching, for instance to boost entries for open files or
+    /// penalize generated files. The returned value is added to the score
+    /// given by the fuzzy matcher.
+    pub fn set_score_bonus(
+        &mut self,
+        f: impl Fn(&Entry<T>) -> i32 + 'static,
+    ) {
+        self.score_bonus = Some(Box::new(f));
+        self.search();
+    }
+
+    /// Configures how terms in the query are matched. Terms using the
+    /// special syntax described in the help screen (such as `'foo` or
+    /// `^foo`) are always matched as that syntax describes.
+    pub fn set_match_mode(&mut self, match_mode: MatchMode) {
+        self.match_mode = match_mode;
+        self.search();
+    }
+
+    /// Configures how search results with the same score are ordered.
+    pub fn set_tiebreak(&mut self, tiebreak: Tiebreak) {
+        self.tiebreak = tiebreak;
+        self.search();
+    }
+
+    /// Selects the rules used to order search results with the same score
+    /// (see [`RankingProfile`]). The default is [`RankingProfile::V1`].
+    pub fn set_ranking_profile(&mut self, ranking_profile: RankingProfile) {
+        self.ranking_profile = ranking_profile;
+        self.rerank();
+    }
+
+    /// Returns the rules used to order search results with the same
+    /// score (see [`set_ranking_profile`](Self::set_ranking_profile)).
+    pub fn ranking_profile(&self) -> RankingProfile {
+        self.ranking_profile
+    }
+
+    /// Adds a column that search results can be sorted by, with a function
+    /// returning the value of the column for an entry. Columns are shown
+    /// in a header above the list, and pressing `s` followed by the
+    /// number of a column in normal mode sorts the results by that column
+    /// (pressing it again reverses the direction, and `s0` goes back to
+    /// sorting by score). Values are compared like
+    /// [`Tiebreak::Natural`], so that numbers are sorted numerically.
+    pub fn add_sort_column(
+        &mut self,
+        name: impl Into<String>,
+        f: impl Fn(&Entry<T>) -> String + 'static,
+    ) {
+        self.sort_columns.push((name.into(), Box::new(f)));
+        self.needs_redraw = true;
+    }
+
+    /// Sorts search results by the given column (as added with
+    /// [`add_sort_column`](Self::add_sort_column)), or by score if `None`.
+    /// Within each [group](Entry::group), and after pinned entries,
+    /// results are sorted by the column before anything else.
+    pub fn set_sort(&mut self, sort: Option<(usize, SortDirection)>) {
+        self.sort =
+            sort.filter(|(column, _)| *column < self.sort_columns.len());
+        self.preserving_selection(|_| {});
+    }
+
+    /// Returns the column and direction search results are currently sorted
+    /// by, if any.
+    pub fn sort(&self) -> Option<(usize, SortDirection)> {
+        self.sort
+    }
+
+    /// When enabled, entries that were previously selected are listed
+    /// first (most recently selected first) while the query is empty.
+    /// Entries that are not in the selection history keep their usual
+    /// order after them.
+    pub fn set_history_order(&mut self, history_order: bool) {
+        self.history_order = history_order;
+        self.search();
+    }
+
+    /// When set to a nonzero count, up to that many of the most recently
+    /// selected entries are shown in a separate "Recent" section above the
+    /// full list while the query is empty. The entries in this section are
+    /// moved there from the full list below it, so each entry is still only
+    /// listed once. The default is 0, which disables the section.
+    pub fn set_recent_section(&mut self, count: usize) {
+        self.recent_section = count;
+        self.search();
+    }
+
+    /// Returns the strings of the most recently selected entries, most
+    /// recent first. This can be saved and restored with
+    /// [`set_history`](Self::set_history) to persist the history across
+    /// plugin instances.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Replaces the selection history, most recent first.
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history = history;
+        self.history.truncate(HISTORY_LEN);
+        self.save_state();
+        self.search();
+    }
+
+    /// When set to a nonzero weight, entries which have been chosen often
+    /// or recently are ranked higher. The weight is multiplied by a
+    /// frecency value for each entry (the number of times it was chosen,
+    /// multiplied by up to 8 for entries chosen within the last hour) and
+    /// added to its score. Choosing entries is tracked automatically, and
+    /// can also be recorded with [`record_use`](Self::record_use). The
+    /// default is 0, which disables frecency ranking.
+    pub fn set_frecency_weight(&mut self, weight: u32) {
+        self.frecency_weight = weight;
+        self.search();
+    }
+
+    /// Records that the entry at the given index in
+    /// [`entries`](Self::entries) was used, for frecency ranking.
+    pub fn record_use(&mut self, index: usize) {
+        self.track_use(index);
+        self.save_state();
+    }
+
+    fn track_use(&mut self, index: usize) {
+        let key = self.entry_key(&self.all_entries[index]);
+        let usage = self.usage.entry(key).or_default();
+        usage.count = usage.count.saturating_add(1);
+        usage.last_used = unix_time();
+    }
+
+    /// Returns the usage data collected for frecency ranking, keyed by
+    /// entry key (see [`set_entry_key`](Self::set_entry_key)). This can be
+    /// saved and restored with [`set_usage`](Self::set_usage).
+    pub fn usage(&self) -> &std::collections::HashMap<String, Usage> {
+        &self.usage
+    }
+
+    /// Replaces the usage data used for frecency ranking.
+    pub fn set_usage(
+        &mut self,
+        usage: std::collections::HashMap<String, Usage>,
+    ) {
+        self.usage = usage;
+        self.save_state();
+        self.search();
+    }
+
+    /// Pins the entry with the given key (see
+    /// [`set_entry_key`](Self::set_entry_key)), so that it is listed before
+    /// all unpinned entries whenever it matches the query.
+    pub fn pin(&mut self, key: impl Into<String>) {
+        self.pinned.insert(key.into());
+        self.save_state();
+        self.search();
+    }
+
+    /// Unpins the entry with the given key.
+    pub fn unpin(&mut self, key: &str) {
+        self.pinned.remove(key);
+        self.save_state();
+        self.search();
+    }
+
+    /// Returns the keys of the pinned entries.
+    pub fn pinned(&self) -> impl Iterator<Item = &str> {
+        self.pinned.iter().map(|key| key.as_str())
+    }
+
+    /// Persists the selection history, the usage data for frecency ranking
+    /// and the pinned entries in the given store (such as a
+    /// [`FileStore`]), so that they survive the plugin being closed. Any
+    /// state already in the store is loaded immediately, replacing the
+    /// current state, and the store is updated whenever the state changes.
+    /// Errors reading from or writing to the store are ignored, since the
+    /// picker works fine without its saved state.
+    ///
+    /// Entries whose strings or keys contain newlines aren't saved.
+    pub fn set_store(&mut self, store: impl Store + 'static) {
+        let load = |key| {
+            store
+                .load(key)
+                .ok()
+                .flatten()
+                .map(|data| String::from_utf8_lossy(&data).into_owned())
+        };
+        if let Some(history) = load("history") {
+            self.history = history.lines().map(str::to_string).collect();
+            self.history.truncate(HISTORY_LEN);
+        }
+        if let Some(usage) = load("usage") {
+            self.usage = usage
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.splitn(3, '\t');
+                    let count = fields.next()?.parse().ok()?;
+                    let last_used = fields.next()?.parse().ok()?;
+                    let key = fields.next()?;
+                    Some((key.to_string(), Usage { count, last_used }))
+                })
+                .collect();
+        }
+        if let Some(pinned) = load("pinned") {
+            self.pinned = pinned.lines().map(str::to_string).collect();
+        }
+        self.store = Some(Box::new(store));
+        self.search();
+    }
+
+    fn save_state(&mut self) {
+        let Some(store) = &mut self.store else {
+            return;
+        };
+        let saveable = |s: &&String| !s.contains('\n');
+
+        let mut history = String::new();
+        for string in self.history.iter().filter(saveable) {
+            writeln!(history, "{string}").unwrap();
+        }
+        let mut usage = String::new();
+        for (key, Usage { count, last_used }) in
+            self.usage.iter().filter(|(key, _)| saveable(key))
+        {
+            writeln!(usage, "{count}\t{last_used}\t{key}").unwrap();
+        }
+        let mut pinned: Vec<_> =
+            self.pinned.iter().filter(saveable).collect();
+        pinned.sort();
+        let mut pinned_lines = String::new();
+        for key in pinned {
+            writeln!(pinned_lines, "{key}").unwrap();
+        }
+
+        let _ = store.save("history", history.as_bytes());
+        let _ = store.save("usage", usage.as_bytes());
+        let _ = store.save("pinned", pinned_lines.as_bytes());
+    }
+
+    fn record_history(&mut self, string: String) {
+        self.history.retain(|s| *s != string);
+        self.history.insert(0, string);
+        self.history.truncate(HISTORY_LEN);
+    }
+
+    fn search(&mut self) {
+        if self.batch.depth > 0 {
+            // the results may refer to entries which no longer exist, so
+            // they can't be used until the batch is finished
+            self.capture_batch_selection();
+            self.batch.search = true;
+            self.pending_search = None;
+            self.search_results.clear();
+            self.recent_count = 0;
+            self.update_headers();
+            self.match_count = 0;
+            self.selected = 0;
+            self.needs_redraw = true;
+            return;
+        }
+        let mut search = self.start_search(false, None);
+        self.match_entries(&mut search, usize::MAX);
+        self.finish_search(search);
+    }
+
+    fn start_search(
+        &mut self,
+        narrow: bool,
+        reselect: Option<Option<String>>,
+    ) -> PendingSearch {
+        self.pending_search = None;
+        // the output of a command source was already chosen by the query,
+        // so it isn't filtered any further
+        let query = if self.command_source.is_some() {
+            ""
+        } else {
+            &self.query
+        };
+        self.pattern.reparse(
+            query,
+            self.case_matching,
+            nucleo_matcher::pattern::Normalization::Smart,
+        );
+        self.match_mode.apply(&mut self.pattern);
+
+        // appending to the query can only narrow down the results, so only
+        // the previous results need to be matched again. negations, anchors
+        // and escapes can change the meaning of the text before them, so
+        // those fall back to matching everything.
+        let narrowing = narrow
+            && matches!(
+                self.match_mode,
+                MatchMode::Fuzzy | MatchMode::Substring | MatchMode::Prefix
+            )
+            && !self.match_any_term
+            && self.source_limits.is_empty()
+            && self.query.starts_with(self.searched_query.as_str())
+            && !self.query.contains(['!', '$', '\\'])
+            && self.search_results.len() == self.match_count;
+        let candidates: Option<Vec<_>> = narrowing.then(|| {
+            self.search_results
+                .iter()
+                .map(|search_result| search_result.entry)
+                .collect()
+        });
+
+        let any_term_patterns = if self.match_any_term {
+            query
+                .split_whitespace()
+                .map(|term| {
+                    let mut pattern = nucleo_matcher::pattern::Pattern::parse(
+                        term,
+                        self.case_matching,
+                        nucleo_matcher::pattern::Normalization::Smart,
+                    );
+                    self.match_mode.apply(&mut pattern);
+                    pattern
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        PendingSearch {
+            query: self.query.clone(),
+            total: candidates
+                .as_ref()
+                .map_or(self.all_entries.len(), Vec::len),
+            candidates,
+            any_term_patterns,
+            next: 0,
+            matches: vec![],
+            match_count: 0,
+            duration: std::time::Duration::ZERO,
+            reselect,
+        }
+    }
+
+    fn match_entries(&mut self, search: &mut PendingSearch, count: usize) {
+        let start = std::time::Instant::now();
+        let now = unix_time();
+        let end = search.next.saturating_add(count).min(search.total);
+        let limit = self
+            .result_limit
+            .map(|limit| limit * (self.result_pages + 1));
+
+        for pos in search.next..end {
+            let i = search
+                .candidates
+                .as_ref()
+                .map_or(pos, |candidates| candidates[pos]);
+            if self.hidden.contains(&i) {
+                continue;
+            }
+            let entry = &self.all_entries[i];
+            if entry.current && self.current_entry == CurrentEntry::Hide {
+                continue;
+            }
+            if entry.always_show {
+                search.match_count += 1;
+                search.matches.push(SearchResult {
+                    entry: i,
+                    score: 0,
+                    indices: vec![],
+                });
+                continue;
+            }
+
+            // Utf32Str::new falls back to matching bytes whenever every
+            // grapheme starts with an ascii character, which would make
+            // the indices of strings with combining marks byte offsets
+            let haystack = if entry.string.is_ascii() {
+                nucleo_matcher::Utf32Str::Ascii(entry.string.as_bytes())
+            } else {
+                self.haystack_buf.clear();
+                self.haystack_buf
+                    .extend(nucleo_matcher::chars::graphemes(&entry.string));
+                nucleo_matcher::Utf32Str::Unicode(&self.haystack_buf)
+            };
+            let mut best: Option<SearchResult> = None;
+            if search.any_term_patterns.is_empty() {
+                let mut indices = vec![];
+                best = self
+                    .pattern
+                    .indices(haystack, &mut self.matcher, &mut indices)
+                    .map(|score| SearchResult {
+                        entry: i,
+                        score,
+                        indices,
+                    });
+            } else {
+                for pattern in &search.any_term_patterns {
+                    let mut indices = vec![];
+                    let Some(score) = pattern.indices(
+                        haystack,
+                        &mut self.matcher,
+                        &mut indices,
+                    ) else {
+                        continue;
+                    };
+                    if best.as_ref().is_none_or(|best| score > best.score) {
+                        best = Some(SearchResult {
+                            entry: i,
+                            score,
+                            indices,
+                        });
+                    }
+                }
+            }
+            let Some(mut search_result) = best else {
+                continue;
+            };
+
+            if let Some(score_bonus) = &self.score_bonus {
+                let bonus = score_bonus(entry);
+                search_result.score =
+                    search_result.score.saturating_add_signed(bonus);
+            }
+            if self.frecency_weight > 0 && !self.usage.is_empty() {
+                if let Some(usage) = self.usage.get(&self.entry_key(entry)) {
+                    search_result.score = search_result.score.saturating_add(
+                        usage
+                            .frecency(now)
+                            .saturating_mul(self.frecency_weight),
+                    );
+                }
+            }
+            search.match_count += 1;
+            search.matches.push(search_result);
+
+            // only keep the best results around, so that the cost of
+            // sorting doesn't depend on the total number of matches
+            if let Some(limit) = limit {
+                if search.matches.len() >= limit.saturating_mul(2).max(1) {
+                    self.sort_search_results(&mut search.matches);
+                    search.matches.truncate(limit);
+                }
+            }
+        }
+
+        search.next = end;
+        search.duration += start.elapsed();
+    }
+
+    fn finish_search(&mut self, mut search: PendingSearch) {
+        self.pending_search = None;
+        self.metrics.searches += 1;
+        self.sort_search_results(&mut search.matches);
+        if !self.source_limits.is_empty() {
+            let len = search.matches.len();
+            let mut counts = std::collections::HashMap::new();
+            search.matches.retain(|search_result| {
+                let Some((source, &limit)) = self.all_entries
+                    [search_result.entry]
+                    .source
+                    .as_deref()
+                    .and_then(|source| {
+                        self.source_limits.get_key_value(source)
+                    })
+                else {
+                    return true;
+                };
+                let count = counts.entry(source).or_insert(0);
+                *count += 1;
+                *count <= limit
+            });
+            search.match_count -= len - search.matches.len();
+        }
+        if let Some(limit) = self.result_limit {
+            search.matches.truncate(limit * (self.result_pages + 1));
+        }
+        self.search_results = search.matches;
+        self.match_count = search.match_count;
+        self.searched_query = search.query;
+
+        self.recent_count = 0;
+        if self.recent_section > 0 && self.query.is_empty() {
+            // the recent entries are moved to the front of the results
+            // rather than copied there, so that every entry still has a
+            // single rank
+            let mut by_string = std::collections::HashMap::new();
+            for (rank, search_result) in
+                self.search_results.iter().enumerate()
+            {
+                by_string
+                    .entry(
+                        self.all_entries[search_result.entry].string.as_ref(),
+                    )
+                    .or_insert(rank);
+            }
+            let recent: Vec<_> = self
+                .history
+                .iter()
+                .filter_map(|string| by_string.remove(string.as_str()))
+                .take(self.recent_section)
+                .collect();
+            self.recent_count = recent.len();
+            let mut search_results: Vec<_> =
+                std::mem::take(&mut self.search_results)
+                    .into_iter()
+                    .map(Some)
+                    .collect();
+            self.search_results = recent
+                .into_iter()
+                .filter_map(|rank| search_results[rank].take())
+                .collect();
+            self.search_results
+                .extend(search_results.into_iter().flatten());
+        }
+        self.update_headers();
+
+        self.metrics.last_search_duration = search.duration;
+        self.metrics.entries_scanned = search.total;
+        self.metrics.matches_found = self.match_count;
+        self.needs_redraw = true;
+
+        if let Some(reselect) = search.reselect {
+            self.selected = reselect
+                .and_then(|key| {
+                    self.search_results.iter().position(|search_result| {
+                        self.entry_key(&self.all_entries[search_result.entry])
+                            == key
+                    })
+                })
+                .unwrap_or(0);
+        }
+    }
+
+    fn continue_search(&mut self) {
+        self.search_timer_set = false;
+        let Some(mut search) = self.pending_search.take() else {
+            return;
+        };
+        self.match_entries(
+            &mut search,
+            self.search_chunk_size.unwrap_or(usize::MAX),
+        );
+        self.schedule_search(search);
+    }
+
+    fn finish_pending_search(&mut self) {
+        if let Some(mut search) = self.pending_search.take() {
+            self.match_entries(&mut search, usize::MAX);
+            self.finish_search(search);
+        }
+    }
+
+    fn schedule_search(&mut self, search: PendingSearch) {
+        if search.next < search.total {
+            self.pending_search = Some(search);
+            if !self.search_timer_set {
+                set_timeout(0.0);
+                self.search_timer_set = true;
+            }
+            self.needs_redraw = true;
+        } else {
+            self.finish_search(search);
+        }
+    }
+
+    fn sort_search_results(&self, search_results: &mut [SearchResult]) {
+        let history_ranks: std::collections::HashMap<_, _> =
+            if self.history_order && self.query.is_empty() {
+                self.history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, string)| (string.as_str(), i))
+                    .collect()
+            } else {
+                std::collections::HashMap::new()
+            };
+        let mut groups = std::collections::HashMap::new();
+        for group in self
+            .all_entries
+            .iter()
+            .filter_map(|entry| entry.group.as_deref())
+        {
+            let len = groups.len();
+            groups.entry(group).or_insert(len + 1);
+        }
+        let prefix = self
+            .prefix_first
+            .then(|| self.query.trim().to_lowercase())
+            .filter(|prefix| !prefix.is_empty());
+        let query_terms = if self.prefer_exact_case {
+            query_terms(&self.query)
+        } else {
+            vec![]
+        };
+        search_results.sort_by_cached_key(|search_result| {
+            let entry = &self.all_entries[search_result.entry];
+            SearchResultWithString {
+                sort_value: self
+                    .sort
+                    .map(|(column, _)| (self.sort_columns[column].1)(entry)),
+                descending: self.sort.is_some_and(|(_, direction)| {
+                    direction == SortDirection::Descending
+                }),
+                group: entry
+                    .group
+                    .as_deref()
+                    .map_or(0, |group| groups[group]),
+                always_show: entry.always_show,
+                pinned: !self.pinned.is_empty()
+                    && self.pinned.contains(&self.entry_key(entry)),
+                source_priority: entry
+                    .source
+                    .as_deref()
+                    .and_then(|source| self.source_priorities.get(source))
+                    .copied()
+                    .unwrap_or(0),
+                demoted: entry.current
+                    && self.current_entry == CurrentEntry::Last,
+                history_rank: history_ranks
+                    .get(entry.string.as_ref())
+                    .copied()
+                    .unwrap_or(usize::MAX),
+                prefix: prefix.as_ref().is_some_and(|prefix| {
+                    entry.string.to_lowercase().starts_with(prefix)
+                }),
+                exact_case: self.prefer_exact_case
+                    && is_exact_case(
+                        entry,
+                        &search_result.indices,
+                        &query_terms,
+                    ),
+                score: search_result.score,
+                tiebreak: self.tiebreak,
+                first_index: search_result.indices.first().copied(),
+                // without a query there is nothing to compare lengths to
+                length: match self.ranking_profile {
+                    RankingProfile::V2
+                        if !search_result.indices.is_empty() =>
+                    {
+                        entry.string.graphemes(true).count()
+                    }
+                    _ => 0,
+                },
+                entry: search_result.entry,
+                string: &entry.string,
+            }
+        });
+    }
+
+    fn rerank(&mut self) {
+        let selected = self.selected_key();
+        let mut search_results = std::mem::take(&mut self.search_results);
+        self.sort_search_results(&mut search_results[self.recent_count..]);
+        self.search_results = search_results;
+        self.update_headers();
+        if let Some(key) = selected {
+            self.selected = self
+                .search_results
+                .iter()
+                .position(|search_result| {
+                    self.entry_key(&self.all_entries[search_result.entry])
+                        == key
+                })
+                .unwrap_or(0);
+        }
+        self.needs_redraw = true;
+    }
+
+    fn entry_key(&self, entry: &Entry<T>) -> String {
+        if let Some(entry_key) = &self.entry_key {
+            entry_key(entry)
+        } else {
+            entry.string.to_string()
+        }
+    }
+
+    fn selected_key(&self) -> Option<String> {
+        self.search_results.get(self.selected).map(|search_result| {
+            self.entry_key(&self.all_entries[search_result.entry])
+        })
+    }
+
+    fn mark_new_entries(&mut self, start: usize) {
+        let Some(duration) = self.highlight_new else {
+            return;
+        };
+        if start == 0 && self.known_keys.is_empty() {
+            // the initial list of entries
+            return;
+        }
+
+        let mut known: std::collections::HashSet<_> = self.all_entries
+            [..start]
+            .iter()
+            .map(|entry| self.entry_key(entry))
+            .collect();
+        known.extend(self.known_keys.iter().cloned());
+        let now = std::time::Instant::now();
+        let mut marked = false;
+        for entry in &self.all_entries[start..] {
+            let key = self.entry_key(entry);
+            if !known.contains(&key) {
+                self.new_entries.insert(key, now);
+                marked = true;
+            }
+        }
+        if marked {
+            set_timeout(duration.as_secs_f64());
+            self.needs_redraw = true;
+        }
+    }
+
+    fn expire_new_entries(&mut self) {
+        let Some(duration) = self.highlight_new else {
+            return;
+        };
+        let len = self.new_entries.len();
+        self.new_entries
+            .retain(|_, added_at| added_at.elapsed() < duration);
+        if self.new_entries.len() != len {
+            self.needs_redraw = true;
+        }
+    }
+
+    fn capture_batch_selection(&mut self) {
+        if self.batch.reselect.is_none() {
+            self.batch.reselect = Some(
+                self.pending_selection
+                    .take()
+                    .or_else(|| self.selected_key()),
+            );
+            self.batch.anchor = self.selected_row();
+        }
+    }
+
+    // called by the functions which change the entries or the query, so
+    // that calling several of them from the plugin's update function (such
+    // as clear followed by extend) only searches once
+    fn defer_after_update(&mut self) {
+        if self.batch.after_update && !self.batch.deferred {
+            self.batch.deferred = true;
+            self.batch.depth += 1;
+        }
+    }
+
+    fn finish_update_batch(&mut self) {
+        self.batch.after_update = false;
+        if std::mem::take(&mut self.batch.deferred) {
+            self.batch.depth -= 1;
+            if self.batch.depth == 0 {
+                self.finish_batch();
+            }
+        }
+    }
+
+    fn finish_batch(&mut self) {
+        let batch = std::mem::take(&mut self.batch);
+        if batch.search {
+            self.search();
+            match batch.reselect.flatten() {
+                Some(key) => {
+                    let rank = self.search_results.iter().position(
+                        |search_result| {
+                            self.entry_key(
+                                &self.all_entries[search_result.entry],
+                            ) == key
+                        },
+                    );
+                    self.selected = rank.unwrap_or(0);
+                    // like clear, remember the selection until entries
+                    // are added again
+                    if rank.is_none() && self.all_entries.is_empty() {
+                        self.pending_selection = Some(key);
+                    }
+                    if let (Some(_), Some(prev_row)) = (rank, batch.anchor) {
+                        self.anchor_selected_row(prev_row);
+                    }
+                }
+                None => self.selected = 0,
+            }
+            self.report_selection_change();
+        } else if batch.query {
+            self.query_changed();
+        }
+    }
+
+    fn preserving_selection(&mut self, f: impl FnOnce(&mut Self)) {
+        self.defer_after_update();
+        if self.batch.depth > 0 {
+            self.capture_batch_selection();
+            f(self);
+            self.search();
+            return;
+        }
+
+        let prev_selected = self
+            .pending_selection
+            .take()
+            .or_else(|| self.selected_key());
+        let prev_row = self.selected_row();
+
+        f(self);
+        self.search();
+
+        if let Some(prev_selected) = prev_selected {
+            let rank = self.search_results.iter().position(|search_result| {
+                self.entry_key(&self.all_entries[search_result.entry])
+                    == prev_selected
+            });
+            self.selected = rank.unwrap_or(0);
+            if let (Some(_), Some(prev_row)) = (rank, prev_row) {
+                self.anchor_selected_row(prev_row);
+            }
+        } else {
+            self.selected = 0;
+        }
+
+        self.report_selection_change();
+    }
+
+    // the row of the list the selected entry is displayed on, counting
+    // section headers
+    fn selected_row(&self) -> Option<usize> {
+        if !self.anchor_selection
+            || self.selected >= self.search_results.len()
+        {
+            return None;
+        }
+        Some(
+            self.selected
+                + self
+                    .headers
+                    .iter()
+                    .filter(|(rank, _)| *rank <= self.selected)
+                    .count(),
+        )
+    }
+
+    fn anchor_selected_row(&mut self, prev_row: usize) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        self.scroll_offset =
+            (self.scroll_offset + row).saturating_sub(prev_row);
+        self.needs_redraw = true;
+    }
+
+    fn edit_query(&mut self) {
+        if self.coalesce_input {
+            self.query_edited = true;
+            self.needs_redraw = true;
+        } else {
+            self.query_changed();
+        }
+    }
+
+    fn query_changed(&mut self) {
+        self.query_edited = false;
+        self.schedule_command_source();
+        if self.batch.depth > 0 {
+            self.batch.query = true;
+            self.needs_redraw = true;
+            return;
+        }
+        self.result_pages = 0;
+        let reselect = if self.keep_selection_on_search {
+            self.pending_selection
+                .take()
+                .or_else(|| self.selected_key())
+        } else {
+            None
+        };
+        let mut search = self.start_search(true, Some(reselect));
+        self.match_entries(
+            &mut search,
+            self.search_chunk_size.unwrap_or(usize::MAX),
+        );
+        self.schedule_search(search);
+    }
+
+    fn schedule_command_source(&mut self) {
+        let Some(command_source) = &mut self.command_source else {
+            return;
+        };
+        command_source.due =
+            Some(std::time::Instant::now() + command_source.debounce);
+        set_timeout(command_source.debounce.as_secs_f64());
+        self.needs_redraw = true;
+    }
+
+    fn run_command_source(&mut self) {
+        let Some(command_source) = &mut self.command_source else {
+            return;
+        };
+        command_source.due = None;
+        command_source.started += 1;
+        let args: Vec<_> = command_source
+            .command
+            .iter()
+            .map(|arg| arg.replace("{q}", &self.query))
+            .collect();
+        run_command(
+            &args.iter().map(String::as_str).collect::<Vec<_>>(),
+            std::collections::BTreeMap::from([
+                (
+                    COMMAND_CONTEXT_KEY.to_string(),
+                    command_source.started.to_string(),
+                ),
+                (
+                    COMMAND_SOURCE_CONTEXT_KEY.to_string(),
+                    command_source.id.to_string(),
+                ),
+            ]),
+        );
+        self.needs_redraw = true;
+    }
+
+    fn handle_command_output(
+        &mut self,
+        stdout: &[u8],
+        context: &std::collections::BTreeMap<String, String>,
+    ) {
+        let Some(command_source) = &mut self.command_source else {
+            return;
+        };
+        // other pickers in the same plugin may be running commands too
+        if context
+            .get(COMMAND_SOURCE_CONTEXT_KEY)
+            .is_some_and(|id| *id != command_source.id.to_string())
+        {
+            return;
+        }
+        let Some(generation) = context
+            .get(COMMAND_CONTEXT_KEY)
+            .and_then(|generation| generation.parse().ok())
+        else {
+            return;
+        };
+        command_source.finished = command_source.finished.max(generation);
+        self.needs_redraw = true;
+        // zellij has no way to stop a command once it has started, so the
+        // output of commands which were run for an older query is dropped
+        if generation != command_source.started
+            || command_source.due.is_some()
+        {
+            return;
+        }
+        let entries =
+            (command_source.output)(&String::from_utf8_lossy(stdout));
+        self.batch(|picker| {
+            picker.clear();
+            picker.extend(entries);
+        });
+    }
+
+    fn report_selection_change(&mut self) {
+        if self.batch.depth > 0 {
+            return;
+        }
+        let current =
+            self.search_results.get(self.selected).map(|search_result| {
+                let entry = &self.all_entries[search_result.entry];
+                (search_result.entry, self.entry_key(entry))
+            });
+        if current.as_ref().map(|(_, key)| key)
+            == self.reported_selection.as_ref().map(|(_, key)| key)
+        {
+            self.reported_selection = current;
+            self.reported_rank =
+                Some((self.selected, self.results_generation));
+            return;
+        }
+
+        // the previously selected entry may have been removed from the list
+        // since it was reported
+        let prev = self.reported_selection.as_ref().and_then(|(idx, key)| {
+            self.all_entries
+                .get(*idx)
+                .is_some_and(|entry| self.entry_key(entry) == *key)
+                .then_some(*idx)
+        });
+        if let Some(prev) = prev {
+            let prev = &self.all_entries[prev];
+            if let Some(hook) = &mut self.before_selection_change {
+                hook(prev);
+            }
+        }
+
+        if let Some((entry, _)) = current {
+            if let Some(hook) = &mut self.after_selection_change {
+                let neighbor = |idx: Option<usize>| {
+                    idx.and_then(|idx| self.search_results.get(idx)).map(
+                        |search_result| {
+                            &self.all_entries[search_result.entry]
+                        },
+                    )
+                };
+                hook(SelectionChange {
+                    entry: &self.all_entries[entry],
+                    prev: neighbor(self.selected.checked_sub(1)),
+                    next: neighbor(Some(self.selected + 1)),
+                });
+            }
+        }
+
+        // moving to a neighboring entry isn't a jump, but anything else
+        // (including the results changing under the selection) is
+        let rank = (self.selected, self.results_generation);
+        let adjacent =
+            self.reported_rank.is_some_and(|(prev, generation)| {
+                generation == rank.1 && prev.abs_diff(rank.0) == 1
+            });
+        if let Some((_, key)) = &current {
+            // moving through the jumplist shouldn't change it
+            if !adjacent
+                && self.jumplist.get(self.jumplist_position) != Some(key)
+            {
+                if let Some((_, prev)) = &self.reported_selection {
+                    self.record_jump(prev.clone());
+                }
+                self.record_jump(key.clone());
+            }
+        }
+        self.reported_selection = current;
+        self.reported_rank = Some(rank);
+    }
+
+    fn record_jump(&mut self, key: String) {
+        if self.jumplist.get(self.jumplist_position) == Some(&key) {
+            return;
+        }
+        self.jumplist.truncate(self.jumplist_position + 1);
+        self.jumplist.retain(|jump| *jump != key);
+        self.jumplist.push(key);
+        if self.jumplist.len() > JUMPLIST_LEN {
+            self.jumplist.remove(0);
+        }
+        self.jumplist_position = self.jumplist.len() - 1;
+    }
+
+    fn jump(&mut self, mut positions: impl Iterator<Item = usize>) {
+        // entries which no longer match the query are skipped
+        let found = positions.find_map(|position| {
+            self.search_results
+                .iter()
+                .position(|search_result| {
+                    self.entry_key(&self.all_entries[search_result.entry])
+                        == self.jumplist[position]
+                })
+                .map(|rank| (position, rank))
+        });
+        if let Some((position, rank)) = found {
+            self.jumplist_position = position;
+            self.select(rank);
+        }
+    }
+
+    // returns `count` rows of the list starting from row `start`, where
+    // the headers are interleaved with the search results
+    fn list_rows<'a>(
+        &self,
+        headers: &'a [(usize, Heading)],
+        start: usize,
+        count: usize,
+    ) -> Vec<ListRow<'a>> {
+        let mut rows = Vec::with_capacity(count);
+        let mut headers = headers.iter().peekable();
+        let mut row = 0;
+        let mut rank = 0;
+        while rows.len() < count && rank < self.search_results.len() {
+            if let Some((_, heading)) =
+                headers.next_if(|(header_rank, _)| *header_rank <= rank)
+            {
+                if row >= start {
+                    rows.push(match heading {
+                        Heading::Section(title) => ListRow::Header(title),
+                        Heading::ScoreBand(band) => ListRow::Separator(band),
+                    });
+                }
+                row += 1;
+                continue;
+            }
+            // skip straight past the entries before the first visible row
+            let run_end = headers
+                .peek()
+                .map_or(self.search_results.len(), |(header_rank, _)| {
+                    *header_rank
+                });
+            let skip = start.saturating_sub(row).min(run_end - rank);
+            row += skip;
+            rank += skip;
+            while rows.len() < count && rank < run_end {
+                rows.push(ListRow::Entry(rank));
+                row += 1;
+                rank += 1;
+            }
+        }
+        rows
+    }
+
+    fn row_style(&self, cols: usize) -> RowStyle {
+        RowStyle {
+            cols,
+            theme: self.theme,
+            ascii: self.ascii,
+            control_chars: self.control_chars,
+            match_highlight: self.match_highlight,
+            keep_match_visible: self.keep_match_visible,
+            truncation_marker: self.truncation_marker.0.clone(),
+            source_styles: self.source_styles.clone(),
+        }
+    }
+
+    fn is_marked(&self, rank: usize) -> bool {
+        self.multi_select
+            && self.marks.contains(&self.entry_key(
+                &self.all_entries[self.search_results[rank].entry],
+            ))
+    }
+
+    fn update_headers(&mut self) {
+        self.headers = self.section_headers();
+        self.results_generation += 1;
+    }
+
+    fn section_headers(&self) -> Vec<(usize, Heading)> {
+        let mut headers = vec![];
+        if self.recent_count > 0 {
+            headers.push((0, Heading::Section("Recent".to_string())));
+        }
+        let best_score = self
+            .search_results
+            .iter()
+            .map(|search_result| search_result.score)
+            .max()
+            .unwrap_or(0);
+        let score_bands = self.score_bands.filter(|_| best_score > 0);
+        let mut prev_band = None;
+        let mut prev_group = None;
+        for (rank, search_result) in self
+            .search_results
+            .iter()
+            .enumerate()
+            .skip(self.recent_count)
+        {
+            let group =
+                self.all_entries[search_result.entry].group.as_deref();
+            if let Some(group) = group.filter(|_| group != prev_group) {
+                headers.push((rank, Heading::Section(group.to_string())));
+            } else if rank == self.recent_count && self.recent_count > 0 {
+                headers.push((rank, Heading::Section("All".to_string())));
+            }
+            prev_group = group;
+
+            if let Some(score_bands) = score_bands {
+                let band = score_bands.band(search_result.score, best_score);
+                if prev_band.is_some_and(|prev_band| prev_band != band) {
+                    headers.push((rank, Heading::ScoreBand(band)));
+                }
+                prev_band = Some(band);
+            }
+        }
+        headers
+    }
+
+    fn render_row(
+        &self,
+        line: &mut String,
+        row: &ListRow,
+        cols: usize,
+    ) -> usize {
+        match *row {
+            ListRow::Header(title) => {
+                let title = truncate_to_width(title, cols);
+                let width = str_width(title, self.control_chars);
+                write!(line, "{}", self.theme.heading.paint(title)).unwrap();
+                width
+            }
+            ListRow::Separator(label) => {
+                let rule = self.glyph("─", "-");
+                let separator = format!("{rule}{rule} {label} ");
+                let separator = truncate_to_width(&separator, cols);
+                let width = str_width(separator, self.control_chars);
+                write!(
+                    line,
+                    "{}",
+                    self.theme.hint.paint(format!(
+                        "{separator}{}",
+                        rule.repeat(cols - width)
+                    ))
+                )
+                .unwrap();
+                cols
+            }
+            ListRow::Entry(rank)
+                if rank % 2 == 1
+                    && self.theme.stripe != TextStyle::default() =>
+            {
+                let mut entry = String::new();
+                let width = self.render_entry(
+                    &mut entry,
+                    &self.search_results[rank],
+                    rank == self.selected,
+                    cols,
+                );
+                // the styles within the entry end by resetting all
+                // attributes, so the stripe has to be started again after
+                // each of them
+                let stripe = self.theme.stripe.paint('\0').to_string();
+                let (start, end) = stripe.split_once('\0').unwrap();
+                write!(
+                    line,
+                    "{start}{}{:pad$}{end}",
+                    entry.replace(end, &format!("{end}{start}")),
+                    "",
+                    pad = cols.saturating_sub(width),
+                )
+                .unwrap();
+                width.max(cols)
+            }
+            ListRow::Entry(rank) => self.render_entry(
+                line,
+                &self.search_results[rank],
+                rank == self.selected,
+                cols,
+            ),
+        }
+    }
+
+    fn render_entry(
+        &self,
+        line: &mut String,
+        search_result: &SearchResult,
+        selected: bool,
+        cols: usize,
+    ) -> usize {
+        let entry = &self.all_entries[search_result.entry];
+        let marked =
+            self.multi_select && self.marks.contains(&self.entry_key(entry));
+
+        if selected {
+            write!(line, "{}", self.theme.selected.paint(">")).unwrap();
+        } else {
+            write!(line, " ").unwrap();
+        }
+        if marked {
+            write!(line, "{}", self.theme.marked.paint("+")).unwrap();
+        } else {
+            write!(line, " ").unwrap();
+        }
+
+        if let Some(row_renderer) = &self.row_renderer {
+            let rendered = row_renderer(&RowContext {
+                entry,
+                selected,
+                marked,
+                indices: &search_result.indices,
+                cols: cols.saturating_sub(2),
+                matched: self.theme.matched,
+                control_chars: self.control_chars,
+            });
+            line.push_str(&rendered);
+            return 2 + ansi_width(&rendered);
+        }
+
+        // keybinding hints are dropped rather than crowding out the entry
+        // itself in narrow panes, including the markers drawn before it and
+        // the truncation markers on either side of it
+        let full_cols = cols;
+        let prefix_cols = 2
+            + if self.source_styles.is_empty() { 0 } else { 2 }
+            + if entry.current { 2 } else { 0 };
+        let reserved_cols = prefix_cols + 2 * self.truncation_margin();
+        let keybinding = entry.keybinding.as_deref().and_then(|keybinding| {
+            let width = str_width(keybinding, self.control_chars);
+            (2 * (width + 1) <= cols.saturating_sub(2)
+                && width + 1 + reserved_cols <= cols)
+                .then_some((keybinding, width))
+        });
+        let cols = keybinding.map_or(cols, |(_, keybinding_width)| {
+            cols - keybinding_width - 1
+        });
+
+        // matched characters can only be highlighted in the string that
+        // was matched
+        let (text, indices): (&str, &[u32]) = match &entry.display {
+            Some(display) => (display, &[]),
+            None => (&entry.string, &search_result.indices),
+        };
+
+        let mut current_col = 2;
+        if !self.source_styles.is_empty() {
+            match entry
+                .source
+                .as_ref()
+                .and_then(|source| self.source_styles.get(source))
+            {
+                Some(style) => {
+                    write!(line, "{} ", style.paint(self.glyph("▌", "|")))
+                        .unwrap();
+                }
+                None => line.push_str("  "),
+            }
+            current_col += 2;
+        }
+        if entry.current {
+            write!(
+                line,
+                "{} ",
+                self.theme.current.paint(self.glyph("●", "*"))
+            )
+            .unwrap();
+            current_col += 2;
+        }
+
+        let highlighted = match self.match_highlight {
+            MatchHighlight::Characters => None,
+            MatchHighlight::Words => Some(matched_words(text, indices)),
+        };
+        let is_matched = |grapheme_idx: usize| match &highlighted {
+            Some(highlighted) => highlighted[grapheme_idx],
+            None => indices.contains(&u32::try_from(grapheme_idx).unwrap()),
+        };
+
+        let is_new = !self.new_entries.is_empty()
+            && self.new_entries.contains_key(&self.entry_key(entry));
+
+        // nucleo matches against the first character of each grapheme
+        // cluster, so match indices are grapheme indices
+        let width = |g| grapheme_width(g, self.control_chars);
+        let margin = self.truncation_margin();
+
+        let mut skip = 0;
+        if self.keep_match_visible
+            && current_col + str_width(text, self.control_chars) > cols
+        {
+            if let Some(&first) = indices.first() {
+                let first = usize::try_from(first).unwrap();
+                let end = cols.saturating_sub(margin);
+                let mut through_match: usize =
+                    text.graphemes(true).take(first + 1).map(width).sum();
+                if current_col + through_match > end {
+                    let room = end.saturating_sub(current_col + margin);
+                    for g in text.graphemes(true).take(first) {
+                        if through_match <= room {
+                            break;
+                        }
+                        through_match -= width(g);
+                        skip += 1;
+                    }
+                    write!(
+                        line,
+                        "{} ",
+                        self.theme.hint.paint(&self.truncation_marker.0)
+                    )
+                    .unwrap();
+                    current_col += margin;
+                }
+            }
+        }
+
+        // only reserve space for the truncation marker if the entry
+        // actually needs to be truncated
+        let end = if current_col
+            + text.graphemes(true).skip(skip).map(width).sum::<usize>()
+            > cols
+        {
+            cols.saturating_sub(margin)
+        } else {
+            cols
+        };
+
+        for (grapheme_idx, g) in text.graphemes(true).enumerate().skip(skip) {
+            let g_width = width(g);
+            if current_col + g_width > end {
+                write!(
+                    line,
+                    " {}",
+                    self.theme.hint.paint(&self.truncation_marker.0)
+                )
+                .unwrap();
+                current_col += margin;
+                break;
+            }
+
+            let g = display_grapheme(g, self.control_chars);
+            if is_matched(grapheme_idx) {
+                write!(line, "{}", self.theme.matched.paint(g)).unwrap();
+            } else if selected {
+                write!(line, "{}", self.theme.selected.paint(g)).unwrap();
+            } else if is_new {
+                write!(line, "{}", self.theme.new_entry.paint(g)).unwrap();
+            } else {
+                line.push_str(&g);
+            }
+
+            current_col += g_width;
+        }
+
+        if let Some((keybinding, keybinding_width)) = keybinding {
+            let pad = full_cols
+                .saturating_sub(keybinding_width)
+                .saturating_sub(current_col);
+            write!(line, "{:pad$}{}", "", self.theme.hint.paint(keybinding))
+                .unwrap();
+            current_col = full_cols;
+        }
+
+        current_col
+    }
+
+    fn help_lines(&self) -> Vec<String> {
+        let case_matching = match self.case_matching {
+            nucleo_matcher::pattern::CaseMatching::Respect => {
+                "case sensitive"
+            }
+            nucleo_matcher::pattern::CaseMatching::Ignore => {
+                "case insensitive"
+            }
+            _ => "case sensitive only if the query contains uppercase",
+        };
+        let match_mode = match self.match_mode {
+            MatchMode::Fuzzy => "fuzzy",
+            MatchMode::Substring => "substring",
+            MatchMode::Prefix => "prefix",
+            MatchMode::Suffix => "suffix",
+            MatchMode::Exact => "exact",
+        };
+        let bindings = self.effective_bindings();
+        let bound_keys = |input_mode: Option<InputMode>| {
+            Action::ALL
+                .iter()
+                .filter_map(|&action| {
+                    let keys: Vec<_> = bindings
+                        .iter()
+                        .filter(|(_, mode, bound)| {
+                            *mode == input_mode && *bound == action
+                        })
+                        .map(|(key, _, _)| key_label(key))
+                        .collect();
+                    (!keys.is_empty())
+                        .then(|| (keys.join(", "), action.description()))
+                })
+                .collect::<Vec<_>>()
+        };
+        let global_keys = bound_keys(None);
+        let normal_keys = bound_keys(Some(InputMode::Normal));
+        let search_keys = bound_keys(Some(InputMode::Search));
+        // editing the query isn't done through actions, but these keys can
+        // still be taken over by bindings
+        let key = KeyWithModifier::new;
+        let ctrl = |bare_key| key(bare_key).with_ctrl_modifier();
+        let alt = |bare_key| key(bare_key).with_alt_modifier();
+        let editing_keys: Vec<_> = [
+            (
+                vec![key(BareKey::Delete)],
+                "delete the character under the cursor",
+            ),
+            (
+                vec![key(BareKey::Left), key(BareKey::Right)],
+                "move the cursor",
+            ),
+            (
+                vec![key(BareKey::Home), ctrl(BareKey::Char('a'))],
+                "move to the start of the query",
+            ),
+            (
+                vec![key(BareKey::End), ctrl(BareKey::Char('e'))],
+                "move to the end of the query",
+            ),
+            (vec![ctrl(BareKey::Char('w'))], "delete the previous word"),
+            (
+                vec![alt(BareKey::Char('b')), ctrl(BareKey::Left)],
+                "move to the previous word",
+            ),
+            (
+                vec![alt(BareKey::Char('f')), ctrl(BareKey::Right)],
+                "move to the next word",
+            ),
+            (vec![alt(BareKey::Char('d'))], "delete the next word"),
+        ]
+        .into_iter()
+        .filter_map(|(keys, description)| {
+            let keys: Vec<_> = keys
+                .iter()
+                .filter(|key| {
+                    !bindings.iter().any(|(bound, mode, _)| {
+                        bound == *key && *mode != Some(InputMode::Normal)
+                    }) && self.bound_action(key).is_none()
+                        && self.registered_action(key).is_none()
+                })
+                .map(key_label)
+                .collect();
+            (!keys.is_empty()).then(|| (keys.join(", "), description))
+        })
+        .collect();
+        let key_width = global_keys
+            .iter()
+            .chain(&normal_keys)
+            .chain(&search_keys)
+            .chain(&editing_keys)
+            .map(|(keys, _)| keys.chars().count() + 1)
+            .chain([14])
+            .max()
+            .unwrap_or(14);
+
+        let section =
+            |title: &str| format!("{}", self.theme.heading.paint(title));
+        let line = |key: &str, description: &str| {
+            format!(
+                "  {}{}",
+                self.theme
+                    .key
+                    .paint(format!("{:<key_width$}", format!("{key} "))),
+                description
+            )
+        };
+        let lines_for = |keys: Vec<(String, &str)>| {
+            keys.into_iter()
+                .map(|(keys, description)| line(&keys, description))
+                .collect::<Vec<_>>()
+        };
+
+        let mut lines = vec![
+            section("Matching"),
+            line("foo", &format!("{match_mode} match ({case_matching})")),
+            line("'foo", "substring match"),
+            line("^foo", "prefix match"),
+            line("foo$", "suffix match"),
+            line("!foo", "exclude entries matching foo"),
+            if self.match_any_term {
+                line("foo bar", "match either foo or bar")
+            } else {
+                line("foo bar", "match both foo and bar")
+            },
+            String::new(),
+            section("Keys"),
+        ];
+        lines.extend(lines_for(global_keys));
+        lines.extend([String::new(), section("Normal mode keys")]);
+        lines.extend(lines_for(normal_keys));
+        if self.digit_shortcuts_active() {
+            lines.extend([
+                line("1-8", "choose the entry at that position"),
+                line("9", "choose the last entry"),
+            ]);
+        } else {
+            lines.push(line("0-9", "search for the typed digit"));
+        }
+        lines.push(line(
+            "f<char>",
+            "jump to the next entry starting with <char>",
+        ));
+        if !self.sort_columns.is_empty() {
+            lines.push(line(
+                "s<digit>",
+                "sort by that column (0 to sort by score)",
+            ));
+        }
+        if self.macros {
+            lines.extend([
+                line("q", "start or stop recording a macro"),
+                line("@", "replay the recorded macro"),
+            ]);
+        }
+        lines.extend([String::new(), section("Search mode keys")]);
+        lines.extend(lines_for(search_keys));
+        lines.extend(lines_for(editing_keys));
+        lines.push(String::new());
+        if !self.action_keys.is_empty() {
+            lines.push(section("Custom keys"));
+            lines.extend(
+                self.action_keys
+                    .iter()
+                    .map(|(key, name)| line(&key_label(key), name)),
+            );
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "{}",
+            self.theme
+                .hint
+                .paint(format!("({})", self.message(Message::CloseHelp)))
+        ));
+        lines
+    }
+
+    fn handle_key(&mut self, key: &KeyWithModifier) -> Option<Response> {
+        if self.showing_help {
+            self.showing_help = false;
+            self.needs_redraw = true;
+            return None;
+        }
+
+        if self.number_prompt.is_some() {
+            return self.handle_number_prompt_key(key);
+        }
+
+        if self.macros
+            && self.input_mode == InputMode::Normal
+            && !self.pending_jump
+            && !self.pending_sort
+            && key.has_no_modifiers()
+        {
+            match key.bare_key {
+                BareKey::Char('q') => {
+                    if self.recording.is_some() {
+                        self.stop_recording();
+                    } else {
+                        self.start_recording();
+                    }
+                    return None;
+                }
+                BareKey::Char('@') => {
+                    return self.replay_macro();
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.push(key.clone());
+        }
+
+        let esc_pressed_at = self.esc_pressed_at.take();
+        if esc_pressed_at.is_some() {
+            self.needs_redraw = true;
+        }
+        if self.double_esc_cancel
+            && key.bare_key == BareKey::Esc
+            && key.has_no_modifiers()
+        {
+            if esc_pressed_at.is_some_and(|pressed_at| {
+                pressed_at.elapsed() < DOUBLE_ESC_TIMEOUT
+            }) {
+                return Some(Response::Cancel);
+            }
+            self.esc_pressed_at = Some(std::time::Instant::now());
+            set_timeout(DOUBLE_ESC_TIMEOUT.as_secs_f64());
+            if self.input_mode == InputMode::Normal {
+                return self.run_action(Action::ClearQuery);
+            }
+        }
+
+        if self.pending_sort {
+            self.pending_sort = false;
+            if let BareKey::Char(c @ '0'..='9') = key.bare_key {
+                if key.has_no_modifiers() {
+                    self.toggle_sort(c.to_digit(10).unwrap());
+                }
+            }
+            return None;
+        }
+
+        if self.pending_jump {
+            self.pending_jump = false;
+            if let BareKey::Char(c) = key.bare_key {
+                if key.has_no_modifiers() {
+                    self.jump_to(c);
+                }
+            }
+            return None;
+        }
+
+        if std::mem::take(&mut self.query_selected) {
+            self.needs_redraw = true;
+            // a restored query is replaced by typing, like selected text
+            if self.only_edits_query(key) {
+                self.query.clear();
+                self.cursor = 0;
+                if !matches!(key.bare_key, BareKey::Char(_)) {
+                    self.edit_query();
+                    return None;
+                }
+            }
+        }
+
+        if let Some(name) = self.registered_action(key) {
+            return Some(Response::Action {
+                name: name.to_string(),
+                entry: self
+                    .search_results
+                    .get(self.selected)
+                    .map(|search_result| search_result.entry),
+            });
+        }
+
+        if let Some(action) = self.bound_action(key) {
+            return action.and_then(|action| self.run_action(action));
+        }
+
+        if let Some(action) = self.builtin_action(key) {
+            return self.run_action(action);
+        }
+
+        match self.input_mode {
+            InputMode::Normal => self.handle_normal_key(key),
+            InputMode::Search => self.handle_search_key(key),
+            InputMode::Custom(idx) => self.handle_custom_key(idx, key),
+        }
+    }
+
+    fn handle_normal_key(
+        &mut self,
+        key: &KeyWithModifier,
+    ) -> Option<Response> {
+        match key.bare_key {
+            BareKey::Char('0'..='9')
+                if key.has_no_modifiers()
+                    && !self.digit_shortcuts_active() =>
+            {
+                self.run_action(Action::EnterSearchMode);
+                return self.handle_search_key(key);
+            }
+            BareKey::Char(c @ '1'..='8') if key.has_no_modifiers() => {
+                let position =
+                    usize::try_from(c.to_digit(10).unwrap() - 1).unwrap();
+                return self.select_rank(position);
+            }
+            BareKey::Char('9') if key.has_no_modifiers() => {
+                return self
+                    .search_results
+                    .len()
+                    .checked_sub(1)
+                    .and_then(|rank| self.select_rank(rank));
+            }
+            BareKey::Char('f') if key.has_no_modifiers() => {
+                self.pending_jump = true;
+            }
+            BareKey::Char('s')
+                if key.has_no_modifiers()
+                    && !self.sort_columns.is_empty() =>
+            {
+                self.pending_sort = true;
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn only_edits_query(&self, key: &KeyWithModifier) -> bool {
+        self.input_mode == InputMode::Search
+            && !self.showing_help
+            && key.has_no_modifiers()
+            && matches!(
+                key.bare_key,
+                BareKey::Char(_) | BareKey::Backspace | BareKey::Delete
+            )
+            && self.registered_action(key).is_none()
+            && self.bound_action(key).is_none()
+    }
+
+    fn digit_shortcuts_active(&self) -> bool {
+        match self.digit_shortcuts {
+            DigitShortcuts::Enabled => true,
+            DigitShortcuts::Auto => !self.all_entries.iter().any(|entry| {
+                entry.string.starts_with(|c: char| c.is_ascii_digit())
+            }),
+            DigitShortcuts::Disabled => false,
+        }
+    }
+
+    fn handle_search_key(
+        &mut self,
+        key: &KeyWithModifier,
+    ) -> Option<Response> {
+        match key.bare_key {
+            BareKey::Char(c) if key.has_no_modifiers() => {
+                self.query.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+                self.edit_query();
+            }
+            BareKey::Delete
+                if key.has_no_modifiers()
+                    && self.cursor < self.query.len() =>
+            {
+                let end = next_grapheme_boundary(&self.query, self.cursor);
+                self.query.replace_range(self.cursor..end, "");
+                self.edit_query();
+            }
+            BareKey::Char('w') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                let start = prev_word_boundary(&self.query, self.cursor);
+                if start < self.cursor {
+                    self.query.replace_range(start..self.cursor, "");
+                    self.cursor = start;
+                    self.edit_query();
+                }
+            }
+            BareKey::Left if key.has_no_modifiers() && self.cursor > 0 => {
+                self.cursor =
+                    prev_grapheme_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Right
+                if key.has_no_modifiers()
+                    && self.cursor < self.query.len() =>
+            {
+                self.cursor =
+                    next_grapheme_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Home if key.has_no_modifiers() => {
+                self.cursor = 0;
+                self.needs_redraw = true;
+            }
+            BareKey::Char('a') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                self.cursor = 0;
+                self.needs_redraw = true;
+            }
+            BareKey::End if key.has_no_modifiers() => {
+                self.cursor = self.query.len();
+                self.needs_redraw = true;
+            }
+            BareKey::Char('e') if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                self.cursor = self.query.len();
+                self.needs_redraw = true;
+            }
+            BareKey::Char('b') if key.has_modifiers(&[KeyModifier::Alt]) => {
+                self.cursor = prev_word_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Left if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                self.cursor = prev_word_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Char('f') if key.has_modifiers(&[KeyModifier::Alt]) => {
+                self.cursor = next_word_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Right if key.has_modifiers(&[KeyModifier::Ctrl]) => {
+                self.cursor = next_word_boundary(&self.query, self.cursor);
+                self.needs_redraw = true;
+            }
+            BareKey::Char('d') if key.has_modifiers(&[KeyModifier::Alt]) => {
+                let end = next_word_boundary(&self.query, self.cursor);
+                if end > self.cursor {
+                    self.query.replace_range(self.cursor..end, "");
+                    self.edit_query();
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn handle_number_prompt_key(
+        &mut self,
+        key: &KeyWithModifier,
+    ) -> Option<Response> {
+        let number_prompt = self.number_prompt.as_mut()?;
+        self.needs_redraw = true;
+        if !key.has_no_modifiers() {
+            return None;
+        }
+        match key.bare_key {
+            BareKey::Char(c @ '0'..='9') => {
+                number_prompt.input.push(c);
+                number_prompt.invalid = false;
+            }
+            BareKey::Char('-')
+                if number_prompt.input.is_empty()
+                    && *number_prompt.range.start() < 0 =>
+            {
+                number_prompt.input.push('-');
+                number_prompt.invalid = false;
+            }
+            BareKey::Backspace => {
+                number_prompt.input.pop();
+                number_prompt.invalid = false;
+            }
+            BareKey::Enter => {
+                match number_prompt
+                    .input
+                    .parse()
+                    .ok()
+                    .filter(|value| number_prompt.range.contains(value))
+                {
+                    Some(value) => {
+                        let index = number_prompt.index;
+                        self.number_prompt = None;
+                        return Some(Response::Number { index, value });
+                    }
+                    None => number_prompt.invalid = true,
+                }
+            }
+            BareKey::Esc => self.number_prompt = None,
+            _ => {}
+        }
+        None
+    }
+
+    fn handle_custom_key(
+        &mut self,
+        idx: usize,
+        key: &KeyWithModifier,
+    ) -> Option<Response> {
+        // the mode is taken out while it runs so that it can be given
+        // mutable access to the picker
+        let mut mode = self.custom_modes[idx].1.take()?;
+        let response = self.batch(|picker| mode.handle_key(picker, key));
+        self.custom_modes[idx].1 = Some(mode);
+        self.needs_redraw = true;
+        response
+    }
+
+    fn builtin_action(&self, key: &KeyWithModifier) -> Option<Action> {
+        self.builtin_bindings()
+            .into_iter()
+            .find(|(bound, input_mode, _)| {
+                bound == key
+                    && input_mode.is_none_or(|mode| mode == self.input_mode)
+            })
+            .map(|(_, _, action)| action)
+    }
+
+    // the key bindings in effect, for listing them on the help screen.
+    // bindings from bind and unbind take precedence over the keymap, which
+    // takes precedence over the built in keys.
+    fn effective_bindings(
+        &self,
+    ) -> Vec<(KeyWithModifier, Option<InputMode>, Action)> {
+        let mut bound = vec![];
+        let mut bindings = vec![];
+        let keymap = self
+            .keymap
+            .bindings()
+            .into_iter()
+            .map(|(key, action)| (key, Some(action)));
+        for (key, action) in self.keybindings.iter().cloned().chain(keymap) {
+            if bound.contains(&key) {
+                continue;
+            }
+            bound.push(key.clone());
+            // unmodified characters are typed into the query in search
+            // mode, so they are only bound in normal mode
+            let input_mode = (matches!(key.bare_key, BareKey::Char(_))
+                && key.has_no_modifiers())
+            .then_some(InputMode::Normal);
+            if let Some(action) = action {
+                bindings.push((key, input_mode, action));
+            }
+        }
+        bindings.extend(
+            self.builtin_bindings()
+                .into_iter()
+                .filter(|(key, _, _)| !bound.contains(key)),
+        );
+        bindings
+    }
+
+    fn builtin_bindings(
+        &self,
+    ) -> Vec<(KeyWithModifier, Option<InputMode>, Action)> {
+        let mut bindings = vec![];
+        if self.multi_select {
+            let mark_key = self
+                .mark_key
+                .clone()
+                .unwrap_or_else(|| KeyWithModifier::new(BareKey::Tab));
+            bindings.push((mark_key, None, Action::ToggleMark));
+        }
+        bindings.extend(builtin_bindings());
+        bindings
+    }
+
+    fn registered_action(&self, key: &KeyWithModifier) -> Option<&str> {
+        if self.is_text_input(key) {
+            return None;
+        }
+        self.action_keys
+            .iter()
+            .find(|(registered, _)| registered == key)
+            .map(|(_, name)| name.as_str())
+    }
+
+    fn bound_action(&self, key: &KeyWithModifier) -> Option<Option<Action>> {
+        if self.is_text_input(key) {
+            return None;
+        }
+        // bindings from bind and unbind are applied on top of the keymap
+        self.keybindings
+            .iter()
+            .find(|(bound, _)| bound == key)
+            .map(|(_, action)| *action)
+            .or_else(|| {
+                self.keymap
+                    .bindings()
+                    .into_iter()
+                    .find(|(bound, _)| bound == key)
+                    .map(|(_, action)| Some(action))
+            })
+    }
+
+    fn is_text_input(&self, key: &KeyWithModifier) -> bool {
+        self.input_mode != InputMode::Normal
+            && matches!(key.bare_key, BareKey::Char(_))
+            && key.has_no_modifiers()
+    }
+
+    fn run_action(&mut self, action: Action) -> Option<Response> {
+        if self.exit_search_on_navigation
+            && self.input_mode == InputMode::Search
+            && matches!(
+                action,
+                Action::Up
+                    | Action::Down
+                    | Action::PageUp
+                    | Action::PageDown
+                    | Action::First
+                    | Action::Last
+            )
+        {
+            self.input_mode = InputMode::Normal;
+        }
+
+        match action {
+            Action::Up => {
+                self.up();
+            }
+            Action::Down => {
+                self.down();
+            }
+            Action::PageUp => {
+                let page = self.list_area.rows.max(1);
+                self.selected = self.selected.saturating_sub(page);
+                self.needs_redraw = true;
+            }
+            Action::PageDown => {
+                let page = self.list_area.rows.max(1);
+                let target = self.selected + page;
+                while target >= self.search_results.len() && self.load_more()
+                {
+                }
+                self.selected =
+                    target.min(self.search_results.len().saturating_sub(1));
+                self.needs_redraw = true;
+            }
+            Action::First => {
+                self.selected = 0;
+                self.needs_redraw = true;
+            }
+            Action::Last => {
+                self.selected = self.search_results.len().saturating_sub(1);
+                self.needs_redraw = true;
+            }
+            Action::Select => {
+                self.finish_pending_search();
+                if self.multi_select && !self.search_results.is_empty() {
+                    return Some(Response::SelectMulti(
+                        self.marked_entries(),
+                    ));
+                }
+                if let Some(response) = self.select_rank(self.selected) {
+                    return Some(response);
+                }
+                return match self.empty_select {
+                    EmptySelect::Ignore => None,
+                    EmptySelect::Cancel => Some(Response::Cancel),
+                    EmptySelect::Query => {
+                        Some(Response::Query(self.query.clone()))
+                    }
+                };
+            }
+            Action::Cancel => {
+                return Some(Response::Cancel);
+            }
+            Action::ClearQuery => {
+                if !self.query.is_empty() {
+                    self.query.clear();
+                    self.cursor = 0;
+                    self.query_changed();
+                }
+            }
+            Action::DeleteChar => {
+                if self.cursor > 0 {
+                    let start =
+                        prev_grapheme_boundary(&self.query, self.cursor);
+                    self.query.replace_range(start..self.cursor, "");
+                    self.cursor = start;
+                    self.edit_query();
+                }
+            }
+            Action::ToggleSearchMode => {
+                self.input_mode = if self.input_mode == InputMode::Search {
+                    InputMode::Normal
+                } else {
+                    InputMode::Search
+                };
+                self.needs_redraw = true;
+            }
+            Action::EnterSearchMode => {
+                self.input_mode = InputMode::Search;
+                self.needs_redraw = true;
+            }
+            Action::ExitSearchMode => {
+                self.input_mode = InputMode::Normal;
+                self.needs_redraw = true;
+            }
+            Action::ToggleMark => {
+                if self.multi_select {
+                    self.toggle_mark();
+                    self.down();
+                }
+            }
+            Action::ShowHelp => {
+                self.showing_help = true;
+                self.needs_redraw = true;
+            }
+            Action::Refresh => {
+                self.refreshing = true;
+                self.needs_redraw = true;
+                if self.command_source.is_some() {
+                    self.run_command_source();
+                }
+                return Some(Response::Refresh);
+            }
+            Action::JumpBack => {
+                // remember where we jumped back from, if the selection was
+                // moved since the last jump
+                if let Some((_, key)) = &self.reported_selection {
+                    self.record_jump(key.clone());
+                }
+                let positions = (0..self.jumplist_position).rev();
+                self.jump(positions);
+            }
+            Action::JumpForward => {
+                let positions =
+                    self.jumplist_position + 1..self.jumplist.len();
+                self.jump(positions);
+            }
+            Action::TogglePreferExactCase => {
+                self.set_prefer_exact_case(!self.prefer_exact_case);
+            }
+        }
+
+        None
+    }
+
+    #[cfg(feature = "mouse")]
+    fn handle_mouse(&mut self, mouse: &Mouse) -> Option<Response> {
+        match *mouse {
+            Mouse::LeftClick(line, col) => {
+                let rank = self.rank_at(line, col)?;
+                self.selected = rank;
+                self.needs_redraw = true;
+                let last_click = self
+                    .last_click
+                    .replace((rank, std::time::Instant::now()));
+                let choose = match self.mouse_select {
+                    MouseSelect::SingleClick => true,
+                    MouseSelect::DoubleClick => {
+                        last_click.is_some_and(|(last_rank, clicked_at)| {
+                            last_rank == rank
+                                && clicked_at.elapsed() < DOUBLE_CLICK_TIMEOUT
+                        })
+                    }
+                };
+                if choose {
+                    self.last_click = None;
+                    self.drag = None;
+                    return self.run_action(Action::Select);
+                }
+                if self.multi_select {
+                    self.drag = Some((rank, self.marks.clone()));
+                }
+            }
+            Mouse::ScrollUp(lines) => {
+                self.selected = self.selected.saturating_sub(lines);
+                self.needs_redraw = true;
+            }
+            Mouse::ScrollDown(lines) => {
+                let target = self.selected + lines;
+                while target >= self.search_results.len() && self.load_more()
+                {
+                }
+                self.selected =
+                    target.min(self.search_results.len().saturating_sub(1));
+                self.needs_redraw = true;
+            }
+            Mouse::Hold(line, col) => {
+                let rank = self.rank_at(line, col)?;
+                let (anchor, marks) = self.drag.as_ref()?;
+                // the results may have shrunk since the drag started
+                let anchor = (*anchor).min(self.search_results.len() - 1);
+                let mut marks = marks.clone();
+                for rank in anchor.min(rank)..=anchor.max(rank) {
+                    let Some(search_result) = self.search_results.get(rank)
+                    else {
+                        continue;
+                    };
+                    let key = self
+                        .entry_key(&self.all_entries[search_result.entry]);
+                    if !marks.contains(&key) {
+                        marks.push(key);
+                    }
+                }
+                self.marks = marks;
+                self.selected = rank;
+                self.needs_redraw = true;
+            }
+            Mouse::RightClick(line, col) => {
+                let rank = self.rank_at(line, col)?;
+                self.selected = rank;
+                self.needs_redraw = true;
+                if self.multi_select {
+                    self.toggle_mark();
+                }
+            }
+            Mouse::Release(..) => {
+                self.drag = None;
+            }
+            // depending on the zellij version, there may be other kinds of
+            // mouse events (such as hovering) which aren't handled
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+
+        None
+    }
+
+    #[cfg(feature = "mouse")]
+    fn rank_at(&self, line: isize, col: usize) -> Option<usize> {
+        let row = usize::try_from(line)
+            .ok()?
+            .checked_sub(self.list_area.top)?;
+        if col >= self.list_area.cols {
+            return None;
+        }
+        // the entries may have changed since the list was last rendered
+        self.list_area
+            .ranks
+            .get(row)
+            .copied()
+            .flatten()
+            .filter(|&rank| rank < self.search_results.len())
+    }
+
+    fn toggle_sort(&mut self, column: u32) {
+        let Some(column) = usize::try_from(column).unwrap().checked_sub(1)
+        else {
+            self.set_sort(None);
+            return;
+        };
+        let direction = match self.sort {
+            Some((sorted, SortDirection::Ascending)) if sorted == column => {
+                SortDirection::Descending
+            }
+            _ => SortDirection::Ascending,
+        };
+        self.set_sort(Some((column, direction)));
+    }
+
+    fn jump_to(&mut self, c: char) {
+        let len = self.search_results.len();
+        let found = (1..=len)
+            .map(|offset| (self.selected + offset) % len)
+            .find(|&rank| {
+                let entry =
+                    &self.all_entries[self.search_results[rank].entry];
+                entry.string.chars().next().is_some_and(|first| {
+                    first.to_lowercase().eq(c.to_lowercase())
+                })
+            });
+        if let Some(rank) = found {
+            self.selected = rank;
+            self.needs_redraw = true;
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(key) = self.selected_key() else {
+            return;
+        };
+        if let Some(idx) = self.marks.iter().position(|mark| *mark == key) {
+            self.marks.remove(idx);
+        } else {
+            self.marks.push(key);
+        }
+        self.needs_redraw = true;
+    }
+
+    fn marked_entries(&self) -> Vec<usize> {
+        let marked = self.marked();
+        if marked.is_empty() {
+            self.search_results
+                .get(self.selected)
+                .map(|search_result| search_result.entry)
+                .into_iter()
+                .collect()
+        } else {
+            marked
+        }
+    }
+
+    fn select_rank(&self, rank: usize) -> Option<Response> {
+        self.search_results
+            .get(rank)
+            .map(|search_result| Response::Select {
+                index: search_result.entry,
+                rank,
+            })
+    }
+
+    fn down(&mut self) {
+        if self.search_results.is_empty() {
+            return;
+        }
+        if self.selected + 1 == self.search_results.len() && self.load_more()
+        {
+            self.selected += 1;
+            return;
+        }
+        self.selected = (self.search_results.len() + self.selected + 1)
+            % self.search_results.len();
+        self.needs_redraw = true;
+    }
+
+    fn up(&mut self) {
+        if self.search_results.is_empty() {
+            return;
+        }
+        self.selected = (self.search_results.len() + self.selected - 1)
+            % self.search_results.len();
+        self.needs_redraw = true;
+    }
+
+    fn write_pipe_output(&self, pipe_id: &str, response: &Response) {
+        let mut output = String::new();
+        match response {
+            Response::Select { index, .. } => {
+                output = format!("{}\n", self.all_entries[*index].string);
+            }
+            Response::SelectMulti(indices) => {
+                for &index in indices {
+                    writeln!(output, "{}", self.all_entries[index].string)
+                        .unwrap();
+                }
+            }
+            Response::Query(query) => {
+                output = format!("{query}\n");
+            }
+            Response::Number { value, .. } => {
+                output = format!("{value}\n");
+            }
+            Response::Cancel
+            | Response::Action { .. }
+            | Response::PermissionDenied
+            | Response::Refresh => {}
+        }
+        if !output.is_empty() {
+            cli_pipe_output(pipe_id, &output);
+        }
+        unblock_cli_pipe_input(pipe_id);
+    }
+}
+
+impl<T: From<String>> Picker<T> {
+    /// This function should be called during your plugin's
+    /// [`pipe`](zellij_tile::ZellijPlugin::pipe) function, and returns
+    /// true if the message was meant for the picker (in which case your
+    /// plugin's [`pipe`](zellij_tile::ZellijPlugin::pipe) function should
+    /// return true as well). Only messages sent to the pipe configured
+    /// with [`set_pipe_name`](Self::set_pipe_name) are handled.
+    ///
+    /// The payload is turned into entries according to the `format`
+    /// argument of the message. With `format=lines` (the default), each
+    /// non-empty line becomes an entry whose data is the line itself. With
+    /// `format=json` (which requires the `json` feature), the payload must
+    /// be a JSON array whose elements are either strings or objects with a
+    /// `text` field and optional `data`, `display`, `tooltip`, `group`,
+    /// `keybinding`, `current` and `always_show` fields.
+    /// Payloads which can't be parsed are ignored.
+    ///
+    /// The `mode` argument chooses whether the entries are added to the
+    /// existing ones (`mode=append`, the default, which works well with
+    /// `zellij pipe` sending its input one line at a time) or replace them
+    /// (`mode=replace`). If the message comes from the command line and has
+    /// the `output=true` argument, the chosen entries are written back to
+    /// that pipe, one per line, once the picker returns a [`Response`].
+    ///
+    /// Several plugins (or scripts) can contribute entries to the same
+    /// picker without interfering with each other by sending a JSON object
+    /// instead (still with `format=json`), which tags the entries with a
+    /// [`source`](Entry::source):
+    ///
+    /// ```json
+    /// {"action": "add", "source": "tabs", "entries": [...]}
+    /// {"action": "replace", "source": "tabs", "entries": [...]}
+    /// {"action": "remove", "source": "tabs", "entries": ["text", ...]}
+    /// {"action": "clear", "source": "tabs"}
+    /// ```
+    ///
+    /// `add` adds entries (in the same format as the JSON array above) from
+    /// the given source, `replace` replaces all entries from that source,
+    /// `remove` removes the entries from that source with the given
+    /// [`string`](Entry::string)s, and `clear` removes all entries from
+    /// that source. Entries from other sources are left alone, and the
+    /// `mode` argument is ignored.
+    pub fn handle_pipe(&mut self, pipe_message: &PipeMessage) -> bool {
+        if self.pipe_name.as_deref() != Some(pipe_message.name.as_str()) {
+            return false;
+        }
+
+        let arg =
+            |name: &str| pipe_message.args.get(name).map(|s| s.as_ref());
+        let update = match (arg("format"), &pipe_message.payload) {
+            (_, None) => Some(PipeUpdate::Entries(vec![])),
+            (None | Some("lines"), Some(payload)) => {
+                Some(PipeUpdate::Entries(
+                    payload
+                        .lines()
+                        .filter(|line| !line.is_empty())
+                        .map(|line| {
+                            Entry::new(
+                                line.to_string(),
+                                line.to_string().into(),
+                            )
+                        })
+                        .collect(),
+                ))
+            }
+            #[cfg(feature = "json")]
+            (Some("json"), Some(payload)) => parse_json_payload(payload),
+            (Some(_), Some(_)) => None,
+        };
+        match update {
+            Some(PipeUpdate::Entries(entries)) => {
+                let replace = arg("mode") == Some("replace");
+                self.batch(|picker| {
+                    if replace {
+                        picker.clear();
+                    }
+                    picker.extend(entries);
+                });
+            }
+            #[cfg(feature = "json")]
+            Some(PipeUpdate::Add {
+                source,
+                entries,
+                replace,
+            }) => {
+                self.batch(|picker| {
+                    if replace {
+                        picker.retain(|entry| {
+                            entry.source.as_ref() != Some(&source)
+                        });
+                    }
+                    picker.extend(
+                        entries
+                            .into_iter()
+                            .map(|entry| entry.with_source(source.clone())),
+                    );
+                });
+            }
+            #[cfg(feature = "json")]
+            Some(PipeUpdate::Remove { source, strings }) => {
+                self.retain(|entry| {
+                    entry.source.as_ref() != Some(&source)
+                        || !strings.iter().any(|s| *s == entry.string)
+                });
+            }
+            None => {}
+        }
+
+        if let PipeSource::Cli(pipe_id) = &pipe_message.source {
+            if arg("output") == Some("true") {
+                if let Some(prev) = self.pipe_output.replace(pipe_id.clone())
+                {
+                    if prev != *pipe_id {
+                        unblock_cli_pipe_input(&prev);
+                    }
+                }
+            } else {
+                unblock_cli_pipe_input(pipe_id);
+            }
+        }
+
+        true
+    }
+
+    /// Fills the picker with the output of an external command, which is
+    /// run again whenever the query changes. This is useful for sources
+    /// which can do the matching better than the picker can, such as a
+    /// "live grep" picker built on
+    /// `set_command_source(["rg", "--files-with-matches", "{q}"], ...)`.
+    ///
+    /// Every `{q}` in the arguments is replaced by the current query (the
+    /// command isn't run through a shell, so the query doesn't need to be
+    /// quoted). The command is only run once the query has stopped changing
+    /// for `debounce`, and each non-empty line of its output becomes an
+    /// entry whose data is the line itself, replacing all existing entries.
+    /// Output from a command which was run for an older query is ignored.
+    /// Since the command already chose the entries based on the query,
+    /// they aren't matched against the query again (use
+    /// [`Tiebreak::SourceOrder`] to list them in the order the command
+    /// printed them). The picker is considered to be
+    /// [searching](Self::is_searching) while waiting for the command.
+    ///
+    /// Your plugin will need the
+    /// [`RunCommands`](PermissionType::RunCommands) permission, and needs
+    /// to pass [`Event::RunCommandResult`] events to
+    /// [`update`](Self::update). Results of commands run by your plugin
+    /// itself are left alone.
+    pub fn set_command_source(
+        &mut self,
+        command: impl IntoIterator<Item = impl Into<String>>,
+        debounce: std::time::Duration,
+    ) {
+        if self.command_source.is_none() {
+            subscribe(&[EventType::Timer, EventType::RunCommandResult]);
+        }
+        self.require_permissions();
+        self.command_source = Some(CommandSource {
+            id: COMMAND_SOURCES
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            command: command.into_iter().map(Into::into).collect(),
+            debounce,
+            output: |stdout| {
+                stdout
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(|line| {
+                        Entry::new(line.to_string(), line.to_string().into())
+                    })
+                    .collect()
+            },
+            due: None,
+            started: 0,
+            finished: 0,
+        });
+        self.run_command_source();
+        self.search();
+    }
+
+    /// Stops running the command set by
+    /// [`set_command_source`](Self::set_command_source). The entries it
+    /// produced are kept, and are matched against the query as usual again.
+    pub fn clear_command_source(&mut self) {
+        if self.command_source.take().is_some() {
+            self.search();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NumberPrompt {
+    index: usize,
+    prompt: String,
+    range: std::ops::RangeInclusive<i64>,
+    input: String,
+    invalid: bool,
+}
+
+struct CommandSource<T> {
+    id: u64,
+    command: Vec<String>,
+    debounce: std::time::Duration,
+    output: CommandOutputFn<T>,
+    due: Option<std::time::Instant>,
+    started: u64,
+    finished: u64,
+}
+
+impl<T> CommandSource<T> {
+    fn is_running(&self) -> bool {
+        self.due.is_some() || self.finished < self.started
+    }
+}
+
+enum PipeUpdate<T> {
+    Entries(Vec<Entry<T>>),
+    #[cfg(feature = "json")]
+    Add {
+        source: String,
+        entries: Vec<Entry<T>>,
+        replace: bool,
+    },
+    #[cfg(feature = "json")]
+    Remove {
+        source: String,
+        strings: Vec<String>,
+    },
+}
+
+/// The payload of a `format=json` pipe message, as documented on
+/// [`Picker::handle_pipe`].
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum JsonPayload {
+    Entries(Vec<JsonEntry>),
+    Update(JsonUpdate),
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum JsonUpdate {
+    Add {
+        source: String,
+        #[serde(default)]
+        entries: Vec<JsonEntry>,
+    },
+    Replace {
+        source: String,
+        #[serde(default)]
+        entries: Vec<JsonEntry>,
+    },
+    Remove {
+        source: String,
+        #[serde(default)]
+        entries: Vec<String>,
+    },
+    Clear {
+        source: String,
+    },
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Text(String),
+    Fields {
+        text: String,
+        data: Option<String>,
+        display: Option<String>,
+        tooltip: Option<String>,
+        group: Option<String>,
+        keybinding: Option<String>,
+        #[serde(default)]
+        current: bool,
+        #[serde(default)]
+        always_show: bool,
+    },
+}
+
+#[cfg(feature = "json")]
+impl<T: From<String>> From<JsonEntry> for Entry<T> {
+    fn from(entry: JsonEntry) -> Self {
+        match entry {
+            JsonEntry::Text(text) => Entry::new(text.clone(), text.into()),
+            JsonEntry::Fields {
+                text,
+                data,
+                display,
+                tooltip,
+                group,
+                keybinding,
+                current,
+                always_show,
+            } => {
+                let data = data.unwrap_or_else(|| text.clone());
+                let mut entry = Entry::new(text, data.into())
+                    .with_current(current)
+                    .with_always_show(always_show);
+                entry.display = display;
+                entry.tooltip = tooltip;
+                entry.group = group;
+                entry.keybinding = keybinding;
+                entry
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn parse_json_payload<T: From<String>>(
+    payload: &str,
+) -> Option<PipeUpdate<T>> {
+    let entries = |entries: Vec<JsonEntry>| {
+        entries.into_iter().map(Entry::from).collect()
+    };
+    Some(match serde_json::from_str(payload).ok()? {
+        JsonPayload::Entries(items) => PipeUpdate::Entries(entries(items)),
+        JsonPayload::Update(JsonUpdate::Add {
+            source,
+            entries: items,
+        }) => PipeUpdate::Add {
+            source,
+            entries: entries(items),
+            replace: false,
+        },
+        JsonPayload::Update(JsonUpdate::Replace {
+            source,
+            entries: items,
+        }) => PipeUpdate::Add {
+            source,
+            entries: entries(items),
+            replace: true,
+        },
+        JsonPayload::Update(JsonUpdate::Remove {
+            source,
+            entries: strings,
+        }) => PipeUpdate::Remove { source, strings },
+        JsonPayload::Update(JsonUpdate::Clear { source }) => {
+            PipeUpdate::Add {
+                source,
+                entries: vec![],
+                replace: true,
+            }
+        }
+    })
+}
+
+#[derive(Debug, Default)]
+struct Batch {
+    depth: usize,
+    // set when update returns, so that the changes the plugin makes to
+    // the entries or the query while handling the same event are batched
+    after_update: bool,
+    // whether the batch opened by such a change is still open
+    deferred: bool,
+    reselect: Option<Option<String>>,
+    anchor: Option<usize>,
+    search: bool,
+    query: bool,
+}
+
+#[derive(Debug)]
+struct TruncationMarker(String);
+
+impl Default for TruncationMarker {
+    fn default() -> Self {
+        Self("[...]".to_string())
+    }
+}
+
+#[derive(Debug)]
+struct Placeholder(String);
+
+impl Default for Placeholder {
+    fn default() -> Self {
+        Self("(press / to search)".to_string())
+    }
+}
+
+// the position of the entries is only needed to handle mouse clicks
+#[cfg_attr(not(feature = "mouse"), allow(dead_code))]
+#[derive(Debug, Default)]
+struct ListArea {
+    top: usize,
+    ranks: Vec<Option<usize>>,
+    cols: usize,
+    rows: usize,
+}
+
+enum Heading {
+    Section(String),
+    ScoreBand(&'static str),
+}
+
+// everything other than the entry itself which affects how a row of the
+// list is rendered
+#[derive(Debug, PartialEq)]
+struct RowStyle {
+    cols: usize,
+    theme: Theme,
+    ascii: bool,
+    control_chars: ControlChars,
+    match_highlight: MatchHighlight,
+    keep_match_visible: bool,
+    truncation_marker: String,
+    source_styles: std::collections::HashMap<String, TextStyle>,
+}
+
+// the entry rows rendered for the previous frame, by rank, whether the
+// entry was selected and whether it was marked
+#[derive(Debug, Default)]
+struct RowCache {
+    generation: u64,
+    style: Option<RowStyle>,
+    lines: std::collections::HashMap<(usize, bool, bool), (String, usize)>,
+}
+
+enum ListRow<'a> {
+    Header(&'a str),
+    Separator(&'a str),
+    Entry(usize),
+}
+
+impl ListRow<'_> {
+    fn rank(&self) -> Option<usize> {
+        match self {
+            Self::Header(_) | Self::Separator(_) => None,
+            Self::Entry(rank) => Some(*rank),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SearchResult {
+    entry: usize,
+    score: u32,
+    indices: Vec<u32>,
+}
+
+#[derive(Debug)]
+struct SearchResultWithString<'a> {
+    group: usize,
+    always_show: bool,
+    pinned: bool,
+    source_priority: i32,
+    sort_value: Option<String>,
+    descending: bool,
+    demoted: bool,
+    history_rank: usize,
+    prefix: bool,
+    exact_case: bool,
+    score: u32,
+    tiebreak: Tiebreak,
+    first_index: Option<u32>,
+    length: usize,
+    entry: usize,
+    string: &'a str,
+}
+
+#[derive(Debug)]
+struct PendingSearch {
+    query: String,
+    candidates: Option<Vec<usize>>,
+    any_term_patterns: Vec<nucleo_matcher::pattern::Pattern>,
+    total: usize,
+    next: usize,
+    matches: Vec<SearchResult>,
+    match_count: usize,
+    duration: std::time::Duration,
+    reselect: Option<Option<String>>,
+}
+
+impl Ord for SearchResultWithString<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.group
+            .cmp(&other.group)
+            .then_with(|| self.always_show.cmp(&other.always_show))
+            .then_with(|| self.pinned.cmp(&other.pinned).reverse())
+            .then_with(|| {
+                self.source_priority.cmp(&other.source_priority).reverse()
+            })
+            .then_with(|| match (&self.sort_value, &other.sort_value) {
+                (Some(a), Some(b)) if self.descending => natural_cmp(b, a),
+                (Some(a), Some(b)) => natural_cmp(a, b),
+                _ => std::cmp::Ordering::Equal,
+            })
+            .then_with(|| self.demoted.cmp(&other.demoted))
+            .then_with(|| self.history_rank.cmp(&other.history_rank))
+            .then_with(|| self.prefix.cmp(&other.prefix).reverse())
+            .then_with(|| self.exact_case.cmp(&other.exact_case).reverse())
+            .then_with(|| self.score.cmp(&other.score).reverse())
+            .then_with(|| match self.tiebreak {
+                Tiebreak::Alphabetical => self
+                    .first_index
+                    .cmp(&other.first_index)
+                    .then_with(|| self.length.cmp(&other.length))
+                    .then_with(|| self.string.cmp(other.string)),
+                Tiebreak::Natural => self
+                    .first_index
+                    .cmp(&other.first_index)
+                    .then_with(|| self.length.cmp(&other.length))
+                    .then_with(|| natural_cmp(self.string, other.string)),
+                Tiebreak::SourceOrder => self.entry.cmp(&other.entry),
+            })
+    }
+}
+
+impl PartialOrd for SearchResultWithString<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Eq for SearchResultWithString<'_> {}
+
+impl PartialEq for SearchResultWithString<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+fn choices_expected<V>(choices: &[(&str, V)]) -> String {
+    choices
+        .iter()
+        .map(|(name, _)| format!("'{name}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// width of a string that may contain ANSI escape sequences, which take up
+// no space
+fn ansi_width(s: &str) -> usize {
+    let mut text = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        text.push(c);
+    }
+    str_width(&text, ControlChars::default())
+}
+
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn push_preview_line(out: &mut String, line: &str, cols: usize) {
+    let mut current_col = 0;
+    for g in line.graphemes(true) {
+        let g = if g == "\t" { " " } else { g };
+        let width = grapheme_width(g, ControlChars::default());
+        if current_col + width > cols {
+            break;
+        }
+        out.push_str(&display_grapheme(g, ControlChars::default()));
+        current_col += width;
+    }
+}
+
+/// Returns the number of columns the given string takes up when rendered
+/// by the picker. Control characters are counted as they are displayed by
+/// default (see [`ControlChars`]).
+pub fn display_width(s: &str) -> usize {
+    str_width(s, ControlChars::default())
+}
+
+/// Returns the longest prefix of the given string which fits in the given
+/// number of columns when rendered by the picker.
+pub fn truncate_to_width(s: &str, cols: usize) -> &str {
+    let mut width = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        width += grapheme_width(g, ControlChars::default());
+        if width > cols {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+fn str_width(s: &str, control_chars: ControlChars) -> usize {
+    s.graphemes(true)
+        .map(|g| grapheme_width(g, control_chars))
+        .sum()
+}
+
+fn grapheme_width(g: &str, control_chars: ControlChars) -> usize {
+    if g.starts_with(char::is_control) {
+        g.chars().map(|c| char_width(c, control_chars)).sum()
+    } else {
+        // terminals draw a cluster (such as an emoji sequence joined with
+        // zero width joiners) as a single glyph, which is at most two
+        // columns wide
+        g.width().min(2)
+    }
+}
+
+/// Returns, for each grapheme in `text`, whether it is part of a word
+/// containing one of the matched graphemes in `indices`. Graphemes outside
+/// of words (whitespace and punctuation) are only included if they matched
+/// themselves.
+fn matched_words(text: &str, indices: &[u32]) -> Vec<bool> {
+    let mut highlighted = vec![];
+    for word in text.split_word_bounds() {
+        let start = highlighted.len();
+        let len = word.graphemes(true).count();
+        let is_word = word.chars().any(char::is_alphanumeric);
+        let any_matched = indices.iter().any(|&idx| {
+            let idx = usize::try_from(idx).unwrap();
+            idx >= start && idx < start + len
+        });
+        highlighted.extend((start..start + len).map(|idx| {
+            if is_word {
+                any_matched
+            } else {
+                indices.contains(&u32::try_from(idx).unwrap())
+            }
+        }));
+    }
+    highlighted
+}
+
+fn display_grapheme(
+    g: &str,
+    control_chars: ControlChars,
+) -> std::borrow::Cow<'_, str> {
+    if g.starts_with(char::is_control) {
+        g.chars()
+            .filter_map(|c| display_char(c, control_chars))
+            .collect::<String>()
+            .into()
+    } else {
+        g.into()
+    }
+}
+
+fn char_width(c: char, control_chars: ControlChars) -> usize {
+    display_char(c, control_chars)
+        .and_then(|c| c.width())
+        .unwrap_or(0)
+}
+
+fn display_char(c: char, control_chars: ControlChars) -> Option<char> {
+    if !c.is_control() {
+        return Some(c);
+    }
+    match control_chars {
+        ControlChars::Replace => Some(match u32::from(c) {
+            // the unicode control pictures block mirrors the c0 controls
+            n @ 0x00..=0x1f => char::from_u32(0x2400 + n).unwrap(),
+            0x7f => '\u{2421}',
+            _ => '\u{fffd}',
+        }),
+        ControlChars::Strip => None,
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// graphemes are classified by their first character, so that combining
+// marks stay attached to the letters they modify
+fn is_word_grapheme(g: &str) -> bool {
+    g.chars().next().is_some_and(is_word_char)
+}
+
+fn prev_grapheme_boundary(s: &str, pos: usize) -> usize {
+    s[..pos]
+        .grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(i, _)| i)
+}
+
+fn next_grapheme_boundary(s: &str, pos: usize) -> usize {
+    s[pos..]
+        .graphemes(true)
+        .next()
+        .map_or(pos, |g| pos + g.len())
+}
+
+fn prev_word_boundary(s: &str, pos: usize) -> usize {
+    let mut boundary = pos;
+    let mut in_word = false;
+    for (i, g) in s[..pos].grapheme_indices(true).rev() {
+        let word = is_word_grapheme(g);
+        if in_word && !word {
+            break;
+        }
+        in_word |= word;
+        boundary = i;
+    }
+    boundary
+}
+
+fn next_word_boundary(s: &str, pos: usize) -> usize {
+    let mut in_word = false;
+    for (i, g) in s[pos..].grapheme_indices(true) {
+        let word = is_word_grapheme(g);
+        if in_word && !word {
+            return pos + i;
+        }
+        in_word |= word;
+    }
+    s.len()
+}
+
+// the characters of each term in the query which are matched against the
+// entries, without the syntax for negation, anchors and escapes. negated
+// terms don't match anything, so they are left out.
+fn query_terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .filter(|term| !term.starts_with('!'))
+        .map(|term| {
+            let term = term.strip_prefix(['^', '\'']).unwrap_or(term);
+            let term = term
+                .strip_suffix('$')
+                .filter(|term| !term.ends_with('\\'))
+                .unwrap_or(term);
+            let mut chars = vec![];
+            let mut escaped = false;
+            for c in term.chars() {
+                if c == '\\' && !escaped {
+                    escaped = true;
+                    continue;
+                }
+                escaped = false;
+                chars.push(c);
+            }
+            chars
+        })
+        .collect()
+}
+
+fn is_exact_case<T>(
+    entry: &Entry<T>,
+    indices: &[u32],
+    query_terms: &[Vec<char>],
+) -> bool {
+    // indices count graphemes, and are sorted. each term matches its
+    // characters in order, so each matched grapheme corresponds to the
+    // next unmatched character of one of the terms.
+    let mut positions = vec![0; query_terms.len()];
+    let mut indices = indices.iter().peekable();
+    entry.string.graphemes(true).zip(0..).all(|(grapheme, i)| {
+        if indices.next_if_eq(&&i).is_none() {
+            return true;
+        }
+        let Some(c) = grapheme.chars().next() else {
+            return true;
+        };
+        let matched = query_terms.iter().zip(&mut positions).find_map(
+            |(term, position)| {
+                let q = *term.get(*position)?;
+                c.to_lowercase().eq(q.to_lowercase()).then(|| {
+                    *position += 1;
+                    q
+                })
+            },
+        );
+        // matches which only differ by normalization (such as an accented
+        // character matching an unaccented one) aren't compared
+        matched.is_none_or(|q| {
+            !c.is_alphabetic() || c.is_uppercase() == q.is_uppercase()
+        })
+    })
+}
+
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (Some(ac), Some(bc))
+                if ac.is_ascii_digit() && bc.is_ascii_digit() =>
+            {
+                let digits = |chars: &mut std::iter::Peekable<
+                    std::str::Chars<'_>,
+                >| {
+                    let mut digits = String::new();
+                    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+                let a_digits = digits(&mut a);
+                let b_digits = digits(&mut b);
+                let a_digits = a_digits.trim_start_matches('0');
+                let b_digits = b_digits.trim_start_matches('0');
+                let ordering = a_digits
+                    .len()
+                    .cmp(&b_digits.len())
+                    .then_with(|| a_digits.cmp(b_digits));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            (Some(ac), Some(bc)) => {
+                let ordering = ac.cmp(&bc);
+                if ordering.is_ne() {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+/// A complete picker for resurrecting sessions which have exited, using
+/// the list of resurrectable sessions from [`Event::SessionUpdate`].
+///
+/// Sessions are listed with the most recently exited first, along with how
+/// long ago they exited. Choosing a session resurrects it and switches to
+/// it, and pressing Ctrl-d (see [`set_delete_key`](Self::set_delete_key))
+/// deletes the selected session instead. Your plugin will need the
+/// [`ReadApplicationState`](PermissionType::ReadApplicationState) and
+/// [`ChangeApplicationState`](PermissionType::ChangeApplicationState)
+/// permissions, and should usually close itself whenever
+/// [`update`](Self::update) returns a response.
+#[cfg(feature = "helpers")]
+pub struct ResurrectPicker {
+    picker: Picker<String>,
+}
+
+#[cfg(feature = "helpers")]
+impl ResurrectPicker {
+    /// This function must be called during your plugin's
+    /// [`load`](zellij_tile::ZellijPlugin::load) function. It also
+    /// subscribes to [`EventType::SessionUpdate`], and to
+    /// [`EventType::PermissionRequestResult`] (see
+    /// [`Picker::require_permissions`]).
+    pub fn load(
+        &mut self,
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) {
+        subscribe(&[EventType::SessionUpdate]);
+        self.picker.load(configuration);
+        self.picker.require_permissions();
+    }
+
+    /// This function must be called during your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function. If a session
+    /// was chosen, it has already been resurrected when this returns
+    /// [`Response::Select`]. Deleting a session doesn't produce a response.
+    pub fn update(&mut self, event: &Event) -> Option<Response> {
+        if let Event::SessionUpdate(_, resurrectable) = event {
+            let mut sessions = resurrectable.clone();
+            sessions.sort_by_key(|(_, age)| *age);
+            self.picker.batch(|picker| {
+                picker.clear();
+                picker.extend(sessions.into_iter().map(|(name, age)| {
+                    Entry::new(format!("{name} ({})", format_age(age)), name)
+                }));
+            });
+        }
+
+        match self.picker.update(event) {
+            Some(Response::Select { index, rank }) => {
+                switch_session(Some(&self.picker.entries()[index].data));
+                Some(Response::Select { index, rank })
+            }
+            Some(Response::Action { name, entry }) if name == "delete" => {
+                if let Some(index) = entry {
+                    let session = self.picker.entries()[index].data.clone();
+                    delete_dead_session(&session);
+                    // the next session update will remove it for real
+                    self.picker.hide_where(|entry| entry.data == session);
+                }
+                None
+            }
+            response => response,
+        }
+    }
+
+    /// This function must be called during your plugin's
+    /// [`render`](zellij_tile::ZellijPlugin::render) function.
+    pub fn render(&mut self, rows: usize, cols: usize) {
+        self.picker.render(rows, cols);
+    }
+
+    /// Returns true if the picker needs to be redrawn (see
+    /// [`Picker::needs_redraw`]).
+    pub fn needs_redraw(&self) -> bool {
+        self.picker.needs_redraw()
+    }
+
+    /// Returns true if the picker needs to be redrawn, and clears the flag
+    /// (see [`Picker::take_needs_redraw`]).
+    pub fn take_needs_redraw(&mut self) -> bool {
+        self.picker.take_needs_redraw()
+    }
+
+    /// Sets the key which deletes the selected session. The default is
+    /// Ctrl-d.
+    pub fn set_delete_key(&mut self, key: KeyWithModifier) {
+        self.picker.action_keys.retain(|(_, name)| name != "delete");
+        self.picker.register_action("delete", key);
+    }
+
+    /// Returns the underlying picker, for further customization. The data
+    /// of each entry is the name of the session.
+    pub fn picker_mut(&mut self) -> &mut Picker<String> {
+        &mut self.picker
+    }
+}
+
+#[cfg(feature = "helpers")]
+impl Default for ResurrectPicker {
+    fn default() -> Self {
+        let mut picker = Picker::<String>::default();
+        picker.set_tiebreak(Tiebreak::SourceOrder);
+        picker.set_entry_key(|entry| entry.data.clone());
+        picker.register_action(
+            "delete",
+            KeyWithModifier::new(BareKey::Char('d')).with_ctrl_modifier(),
+        );
+        Self { picker }
+    }
+}
+
+#[cfg(feature = "helpers")]
+fn format_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs();
+    let (count, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86399 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
+    };
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+/// A complete picker for opening one of the layouts available to the
+/// current session, using the list of layouts from
+/// [`Event::SessionUpdate`] (which includes both zellij's built-in layouts
+/// and the layouts in the configured layout directory).
+///
+/// Choosing a layout opens it in a new tab. Your plugin will need the
+/// [`ReadApplicationState`](PermissionType::ReadApplicationState) and
+/// [`ChangeApplicationState`](PermissionType::ChangeApplicationState)
+/// permissions, and should usually close itself whenever
+/// [`update`](Self::update) returns a response.
+#[cfg(feature = "helpers")]
+#[derive(Default)]
+pub struct LayoutPicker {
+    picker: Picker<usize>,
+    layouts: Vec<LayoutInfo>,
+}
+
+#[cfg(feature = "helpers")]
+impl LayoutPicker {
+    /// This function must be called during your plugin's
+    /// [`load`](zellij_tile::ZellijPlugin::load) function. It also
+    /// subscribes to [`EventType::SessionUpdate`], and to
+    /// [`EventType::PermissionRequestResult`] (see
+    /// [`Picker::require_permissions`]).
+    pub fn load(
+        &mut self,
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) {
+        subscribe(&[EventType::SessionUpdate]);
+        self.picker.load(configuration);
+        self.picker.require_permissions();
+    }
+
+    /// This function must be called during your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function. If a layout
+    /// was chosen, it has already been opened when this returns
+    /// [`Response::Select`].
+    pub fn update(&mut self, event: &Event) -> Option<Response> {
+        if let Event::SessionUpdate(sessions, _) = event {
+            if let Some(session) =
+                sessions.iter().find(|session| session.is_current_session)
+            {
+                self.layouts.clone_from(&session.available_layouts);
+                let entries: Vec<_> = self
+                    .layouts
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, layout)| {
+                        let source = match layout {
+                            LayoutInfo::BuiltIn(_) => "built-in layout",
+                            LayoutInfo::File(_) => "layout file",
+                            LayoutInfo::Url(_) => "layout url",
+                            LayoutInfo::Stringified(_) => "inline layout",
+                        };
+                        Entry::new(layout.name().to_string(), idx)
+                            .with_tooltip(source)
+                    })
+                    .collect();
+                self.picker.batch(|picker| {
+                    picker.clear();
+                    picker.extend(entries);
+                });
+            }
+        }
+
+        let response = self.picker.update(event);
+        if let Some(Response::Select { index, .. }) = response {
+            let layout = self.picker.entries()[index].data;
+            new_tabs_with_layout_info(self.layouts[layout].clone());
+        }
+        response
+    }
+
+    /// This function must be called during your plugin's
+    /// [`render`](zellij_tile::ZellijPlugin::render) function.
+    pub fn render(&mut self, rows: usize, cols: usize) {
+        self.picker.render(rows, cols);
+    }
+
+    /// Returns true if the picker needs to be redrawn (see
+    /// [`Picker::needs_redraw`]).
+    pub fn needs_redraw(&self) -> bool {
+        self.picker.needs_redraw()
+    }
+
+    /// Returns true if the picker needs to be redrawn, and clears the flag
+    /// (see [`Picker::take_needs_redraw`]).
+    pub fn take_needs_redraw(&mut self) -> bool {
+        self.picker.take_needs_redraw()
+    }
+
+    /// Returns the layout with the given index, which is the data of each
+    /// entry in the picker.
+    pub fn layout(&self, idx: usize) -> Option<&LayoutInfo> {
+        self.layouts.get(idx)
+    }
+
+    /// Returns the underlying picker, for further customization. The data
+    /// of each entry is the index of the layout it opens (see
+    /// [`layout`](Self::layout)).
+    pub fn picker_mut(&mut self) -> &mut Picker<usize> {
+        &mut self.picker
+    }
+}
+
+/// Where a [`DirectoryPicker`] opens the chosen directory.
+#[cfg(feature = "helpers")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OpenDirectory {
+    /// Open a terminal pane in the directory. This is the default.
+    #[default]
+    Pane,
+    /// Open a floating terminal pane in the directory.
+    FloatingPane,
+    /// Open a new tab whose working directory is the directory.
+    Tab,
+}
+
+/// A complete picker for jumping back to recently used directories, in the
+/// style of `zoxide`.
+///
+/// Directories are added with [`add`](Self::add), or by piping them to
+/// the plugin (see [`pipe`](Self::pipe)), which allows a shell prompt hook
+/// such as `zellij pipe --name recent_directories -- "$PWD"` to keep the
+/// list up to date. The list is kept in most recently used order and saved
+/// to a [`Store`] (a [`FileStore`] in `/cache` by default), along with the
+/// picker's usage data, so it survives the plugin being closed.
+///
+/// Directories can also be bookmarked, by pressing Alt-m (see
+/// [`set_bookmark_key`](Self::set_bookmark_key)) or with
+/// [`bookmark`](Self::bookmark). Bookmarks are listed above the recently
+/// used directories regardless of when they were last used, and are saved
+/// to the same store.
+///
+/// Choosing a directory opens it in a new pane (see
+/// [`set_open`](Self::set_open)). Your plugin will need the
+/// [`ChangeApplicationState`](PermissionType::ChangeApplicationState)
+/// permission (and [`ReadCliPipes`](PermissionType::ReadCliPipes) to
+/// receive directories from the command line), and should usually close
+/// itself whenever [`update`](Self::update) returns a response.
+#[cfg(feature = "helpers")]
+pub struct DirectoryPicker {
+    picker: Picker<String>,
+    directories: Vec<String>,
+    bookmarks: Vec<String>,
+    pipe_name: String,
+    open: OpenDirectory,
+}
+
+#[cfg(feature = "helpers")]
+impl Default for DirectoryPicker {
+    fn default() -> Self {
+        let mut picker = Picker::default();
+        picker.register_action(
+            "bookmark",
+            KeyWithModifier::new(BareKey::Char('m')).with_alt_modifier(),
+        );
+        Self {
+            picker,
+            directories: vec![],
+            bookmarks: vec![],
+            pipe_name: "recent_directories".to_string(),
+            open: OpenDirectory::default(),
+        }
+    }
+}
+
+#[cfg(feature = "helpers")]
+impl DirectoryPicker {
+    /// This function must be called during your plugin's
+    /// [`load`](zellij_tile::ZellijPlugin::load) function. It loads the
+    /// saved directories from a [`FileStore`] in `/cache`, and subscribes
+    /// to [`EventType::PermissionRequestResult`] (see
+    /// [`Picker::require_permissions`]).
+    pub fn load(
+        &mut self,
+        configuration: &std::collections::BTreeMap<String, String>,
+    ) {
+        // directories are listed most recently used first
+        self.picker.set_tiebreak(Tiebreak::SourceOrder);
+        self.picker.load(configuration);
+        self.picker.require_permissions();
+        self.set_store(FileStore::default());
+    }
+
+    /// This function must be called during your plugin's
+    /// [`update`](zellij_tile::ZellijPlugin::update) function. If a
+    /// directory was chosen, it has already been opened when this returns
+    /// [`Response::Select`], and it has been moved to the top of the list,
+    /// so the returned index refers to its new position. Bookmarking a
+    /// directory doesn't produce a response.
+    pub fn update(&mut self, event: &Event) -> Option<Response> {
+        let mut response = self.picker.update(event);
+        if let Some(Response::Action { name, entry }) = &response {
+            if name == "bookmark" {
+                if let Some(index) = *entry {
+                    let directory = self.picker.entries()[index].data.clone();
+                    if self.bookmarks.contains(&directory) {
+                        self.unbookmark(&directory);
+                    } else {
+                        self.bookmark(directory);
+                    }
+                }
+                return None;
+            }
+        }
+        if let Some(Response::Select { index, .. }) = &mut response {
+            let directory = self.picker.entries()[*index].data.clone();
+            match self.open {
+                OpenDirectory::Pane => open_terminal(&directory),
+                OpenDirectory::FloatingPane => {
+                    open_terminal_floating(&directory, None);
+                }
+                OpenDirectory::Tab => {
+                    let cwd =
+                        directory.replace('\\', "\\\\").replace('"', "\\\"");
+                    new_tabs_with_layout(&format!(
+                        "layout {{ tab cwd=\"{cwd}\" {{ pane; }}; }}"
+                    ));
+                }
+            }
+            self.add(directory.clone());
+            // the chosen directory is now the most recently used one, or
+            // stays where it was among the bookmarks
+            *index = self
+                .picker
+                .entries()
+                .iter()
+                .position(|entry| entry.data == directory)
+                .unwrap_or(0);
+        }
+        response
+    }
+
+    /// This function should be called during your plugin's
+    /// [`pipe`](zellij_tile::ZellijPlugin::pipe) function, and returns
+    /// true if the message was meant for the picker. Each line of the
+    /// payload of messages sent to the `recent_directories` pipe (see
+    /// [`set_pipe_name`](Self::set_pipe_name)) is added as a directory,
+    /// with the first line becoming the most recently used one.
+    pub fn pipe(&mut self, pipe_message: &PipeMessage) -> bool {
+        if pipe_message.name != self.pipe_name {
+            return false;
+        }
+        if let Some(payload) = &pipe_message.payload {
+            for line in payload.lines().rev() {
+                self.add(line);
+            }
+        }
+        if let PipeSource::Cli(pipe_id) = &pipe_message.source {
+            unblock_cli_pipe_input(pipe_id);
+        }
+        true
+    }
+
+    /// This function must be called during your plugin's
+    /// [`render`](zellij_tile::ZellijPlugin::render) function.
+    pub fn render(&mut self, rows: usize, cols: usize) {
+        self.picker.render(rows, cols);
+    }
+
+    /// Returns true if the picker needs to be redrawn (see
+    /// [`Picker::needs_redraw`]).
+    pub fn needs_redraw(&self) -> bool {
+        self.picker.needs_redraw()
+    }
+
+    /// Returns true if the picker needs to be redrawn, and clears the flag
+    /// (see [`Picker::take_needs_redraw`]).
+    pub fn take_needs_redraw(&mut self) -> bool {
+        self.picker.take_needs_redraw()
+    }
+
+    /// Marks a directory as the most recently used one, adding it to the
+    /// list if necessary. Empty strings and strings containing newlines
+    /// are ignored.
+    pub fn add(&mut self, directory: impl Into<String>) {
+        let directory = directory.into();
+        if directory.is_empty() || directory.contains('\n') {
+            return;
+        }
+        self.directories.retain(|known| *known != directory);
+        self.directories.insert(0, directory);
+        self.directories.truncate(DIRECTORIES_LEN);
+        self.save(DIRECTORIES_KEY);
+        self.update_entries();
+    }
+
+    /// Returns the known directories, most recently used first. This
+    /// includes bookmarked directories which have been used.
+    pub fn directories(&self) -> &[String] {
+        &self.directories
+    }
+
+    /// Bookmarks a directory, so that it is always listed first. Bookmarks
+    /// are listed in the order they were added. Empty strings and strings
+    /// containing newlines are ignored.
+    pub fn bookmark(&mut self, directory: impl Into<String>) {
+        let directory = directory.into();
+        if directory.is_empty()
+            || directory.contains('\n')
+            || self.bookmarks.contains(&directory)
+        {
+            return;
+        }
+        self.bookmarks.push(directory);
+        self.save(BOOKMARKS_KEY);
+        self.update_entries();
+    }
+
+    /// Removes the bookmark for a directory. It is still listed with the
+    /// recently used directories if it has been used.
+    pub fn unbookmark(&mut self, directory: &str) {
+        let len = self.bookmarks.len();
+        self.bookmarks.retain(|bookmark| bookmark != directory);
+        if self.bookmarks.len() != len {
+            self.save(BOOKMARKS_KEY);
+            self.update_entries();
+        }
+    }
+
+    /// Returns the bookmarked directories, in the order they were added.
+    pub fn bookmarks(&self) -> &[String] {
+        &self.bookmarks
+    }
+
+    /// Sets the key which bookmarks the selected directory, or removes its
+    /// bookmark. The default is Alt-m.
+    pub fn set_bookmark_key(&mut self, key: KeyWithModifier) {
+        self.picker
+            .action_keys
+            .retain(|(_, name)| name != "bookmark");
+        self.picker.register_action("bookmark", key);
+    }
+
+    /// Saves the directories and bookmarks (and the picker's state, see
+    /// [`Picker::set_store`]) in the given store rather than in `/cache`,
+    /// replacing the current lists with the ones saved there.
+    pub fn set_store(&mut self, store: impl Store + 'static) {
+        let load = |key| {
+            store
+                .load(key)
+                .ok()
+                .flatten()
+                .map(|data| {
+                    String::from_utf8_lossy(&data)
+                        .lines()
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+        self.directories = load(DIRECTORIES_KEY);
+        self.directories.truncate(DIRECTORIES_LEN);
+        self.bookmarks = load(BOOKMARKS_KEY);
+        self.picker.set_store(store);
+        self.update_entries();
+    }
+
+    /// Sets the name of the pipe which directories are sent to. The
+    /// default is `recent_directories`.
+    pub fn set_pipe_name(&mut self, name: impl Into<String>) {
+        self.pipe_name = name.into();
+    }
+
+    /// Configures where the chosen directory is opened.
+    pub fn set_open(&mut self, open: OpenDirectory) {
+        self.open = open;
+    }
+
+    /// Returns the underlying picker, for further customization. The data
+    /// of each entry is the directory it opens.
+    pub fn picker_mut(&mut self) -> &mut Picker<String> {
+        &mut self.picker
+    }
+
+    fn save(&mut self, key: &str) {
+        let Some(store) = &mut self.picker.store else {
+            return;
+        };
+        let list = match key {
+            BOOKMARKS_KEY => &self.bookmarks,
+            _ => &self.directories,
+        };
+        let _ = store.save(key, list.join("\n").as_bytes());
+    }
+
+    fn update_entries(&mut self) {
+        let recent = self
+            .directories
+            .iter()
+            .filter(|directory| !self.bookmarks.contains(directory));
+        let entries: Vec<_> = self
+            .bookmarks
+            .iter()
+            .map(|directory| (directory, "Bookmarks"))
+            .chain(recent.map(|directory| (directory, "Recent")))
+            .map(|(directory, group)| {
+                let entry = Entry::new(directory.clone(), directory.clone());
+                // without bookmarks, the list isn't split into sections
+                if self.bookmarks.is_empty() {
+                    entry
+                } else {
+                    entry.with_group(group)
+                }
+            })
+            .collect();
+        self.picker.batch(|picker| {
+            picker.clear();
+            picker.extend(entries);
+        });
+    }
+}
+
+/// A rectangular part of the pane, in the coordinates used by zellij's mouse
+/// events (see [`Layout`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// First row of the region.
+    pub top: usize,
+    /// First column of the region.
+    pub left: usize,
+    /// Number of rows in the region.
+    pub rows: usize,
+    /// Number of columns in the region.
+    pub cols: usize,
+}
+
+#[cfg(feature = "mouse")]
+impl Region {
+    fn contains(&self, line: isize, col: usize) -> bool {
+        usize::try_from(line).is_ok_and(|line| {
+            (self.top..self.top + self.rows).contains(&line)
+                && (self.left..self.left + self.cols).contains(&col)
+        })
+    }
+}
+
+/// Which side of the pane the sidebar of a [`Layout`] is drawn on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SidebarPosition {
+    /// The sidebar is drawn to the left of the picker.
+    Left,
+    /// The sidebar is drawn to the right of the picker. This is the
+    /// default.
+    #[default]
+    Right,
+}
+
+/// Splits the pane between a [`Picker`] and regions drawn by your plugin,
+/// so that the picker can be surrounded by additional information without
+/// having to keep track of coordinates manually.
+///
+/// The layout consists of an optional header spanning the top of the pane,
+/// and an optional sidebar next to the picker below it. Your plugin
+/// provides closures which draw the header and the sidebar given the number
+/// of rows and columns available to them, and should then call
+/// [`update`](Self::update) and [`render`](Self::render) in place of the
+/// corresponding picker methods. Mouse events are translated into the
+/// coordinates of the picker, and mouse events outside of the picker are
+/// ignored (use [`header_region`](Self::header_region) and
+/// [`sidebar_region`](Self::sidebar_region) to handle them yourself).
+///
+/// ```no_run
+/// let mut layout = zellij_nucleo::Layout::default();
+/// layout.set_header(1, |_, _| "Switch tab".to_string());
+/// layout.set_sidebar(
+///     20,
+///     zellij_nucleo::SidebarPosition::Right,
+///     |rows, _| vec!["│"; rows].join("\n"),
+/// );
+/// let mut picker = zellij_nucleo::Picker::<u32>::default();
+/// layout.render(&mut picker, 24, 80);
+/// ```
+#[derive(Default)]
+pub struct Layout {
+    header: Option<(usize, RegionRendererFn)>,
+    sidebar: Option<(usize, SidebarPosition, RegionRendererFn)>,
+    #[cfg(feature = "mouse")]
+    size: Option<(usize, usize)>,
+}
+
+impl Layout {
+    /// Reserves `rows` rows at the top of the pane, which are drawn by
+    /// calling `render` with the number of rows and columns in the header.
+    /// The returned string should contain at most that many lines,
+    /// separated by newlines, each fitting within that many columns.
+    pub fn set_header(
+        &mut self,
+        rows: usize,
+        render: impl Fn(usize, usize) -> String + 'static,
+    ) {
+        self.header = Some((rows, Box::new(render)));
+    }
+
+    /// Removes the header set by [`set_header`](Self::set_header).
+    pub fn clear_header(&mut self) {
+        self.header = None;
+    }
+
+    /// Reserves `cols` columns on one side of the picker, which are drawn
+    /// by calling `render` with the number of rows and columns in the
+    /// sidebar, like [`set_header`](Self::set_header).
+    pub fn set_sidebar(
+        &mut self,
+        cols: usize,
+        position: SidebarPosition,
+        render: impl Fn(usize, usize) -> String + 'static,
+    ) {
+        self.sidebar = Some((cols, position, Box::new(render)));
+    }
+
+    /// Removes the sidebar set by [`set_sidebar`](Self::set_sidebar).
+    pub fn clear_sidebar(&mut self) {
+        self.sidebar = None;
+    }
+
+    /// Returns the region of a pane of the given size which the header is
+    /// drawn in, if there is a header.
+    pub fn header_region(&self, rows: usize, cols: usize) -> Option<Region> {
+        let (header_rows, _) = self.header.as_ref()?;
+        Some(Region {
+            top: 0,
+            left: 0,
+            rows: (*header_rows).min(rows),
+            cols,
+        })
+    }
+
+    /// Returns the region of a pane of the given size which the sidebar is
+    /// drawn in, if there is a sidebar.
+    pub fn sidebar_region(&self, rows: usize, cols: usize) -> Option<Region> {
+        let (sidebar_cols, position, _) = self.sidebar.as_ref()?;
+        let sidebar_cols = (*sidebar_cols).min(cols);
+        let top = self.header_rows(rows);
+        Some(Region {
+            top,
+            left: match position {
+                SidebarPosition::Left => 0,
+                SidebarPosition::Right => cols - sidebar_cols,
+            },
+            rows: rows - top,
+            cols: sidebar_cols,
+        })
+    }
+
+    /// Returns the region of a pane of the given size which the picker is
+    /// drawn in.
+    pub fn picker_region(&self, rows: usize, cols: usize) -> Region {
+        let top = self.header_rows(rows);
+        let (left, sidebar_cols) = match &self.sidebar {
+            Some((sidebar_cols, position, _)) => {
+                let sidebar_cols = (*sidebar_cols).min(cols);
+                match position {
+                    SidebarPosition::Left => (sidebar_cols, sidebar_cols),
+                    SidebarPosition::Right => (0, sidebar_cols),
+                }
+            }
+            None => (0, 0),
+        };
+        Region {
+            top,
+            left,
+            rows: rows - top,
+            cols: cols - sidebar_cols,
+        }
+    }
+
+    /// Passes `event` on to [`Picker::update`], translating the coordinates
+    /// of mouse events into the picker's region of the pane (as of the most
+    /// recent call to [`render`](Self::render)). Mouse events outside of
+    /// that region are ignored.
+    pub fn update<T>(
+        &self,
+        picker: &mut Picker<T>,
+        event: &Event,
+    ) -> Option<Response> {
+        #[cfg(feature = "mouse")]
+        if let Event::Mouse(mouse) = event {
+            return self.update_mouse(picker, mouse);
+        }
+        picker.update(event)
+    }
+
+    #[cfg(feature = "mouse")]
+    fn update_mouse<T>(
+        &self,
+        picker: &mut Picker<T>,
+        mouse: &Mouse,
+    ) -> Option<Response> {
+        let Some((rows, cols)) = self.size else {
+            return picker.update(&Event::Mouse(*mouse));
+        };
+        let region = self.picker_region(rows, cols);
+        if region.rows == 0 || region.cols == 0 {
+            return None;
+        }
+        let translate = |line: isize, col: usize| {
+            region.contains(line, col).then(|| {
+                (
+                    line - isize::try_from(region.top).unwrap(),
+                    col - region.left,
+                )
+            })
+        };
+        let mouse = match *mouse {
+            Mouse::LeftClick(line, col) => {
+                let (line, col) = translate(line, col)?;
+                Mouse::LeftClick(line, col)
+            }
+            Mouse::RightClick(line, col) => {
+                let (line, col) = translate(line, col)?;
+                Mouse::RightClick(line, col)
+            }
+            Mouse::Hold(line, col) => {
+                // dragging outside of the picker should still extend the
+                // selection as far as possible
+                let line = line.clamp(
+                    isize::try_from(region.top).unwrap(),
+                    isize::try_from(region.top + region.rows - 1).unwrap(),
+                );
+                let col =
+                    col.clamp(region.left, region.left + region.cols - 1);
+                let (line, col) = translate(line, col)?;
+                Mouse::Hold(line, col)
+            }
+            Mouse::Release(line, col) => {
+                let line = line - isize::try_from(region.top).unwrap();
+                Mouse::Release(line, col.saturating_sub(region.left))
+            }
+            mouse => mouse,
+        };
+        picker.update(&Event::Mouse(mouse))
+    }
+
+    /// Draws the header, the sidebar, and `picker` in a pane of the given
+    /// size. This should be called from your plugin's
+    /// [`render`](zellij_tile::ZellijPlugin::render) function in place of
+    /// [`Picker::render`].
+    pub fn render<T>(
+        &mut self,
+        picker: &mut Picker<T>,
+        rows: usize,
+        cols: usize,
+    ) {
+        print!("{}", self.render_to_string(picker, rows, cols));
+    }
+
+    /// Draws the layout like [`render`](Self::render), but returns the
+    /// output rather than printing it.
+    pub fn render_to_string<T>(
+        &mut self,
+        picker: &mut Picker<T>,
+        rows: usize,
+        cols: usize,
+    ) -> String {
+        #[cfg(feature = "mouse")]
+        {
+            self.size = Some((rows, cols));
+        }
+        let mut lines = vec![];
+
+        if let (Some(region), Some((_, render))) =
+            (self.header_region(rows, cols), &self.header)
+        {
+            let header = render(region.rows, region.cols);
+            lines.extend(
+                header
+                    .lines()
+                    .chain(std::iter::repeat(""))
+                    .take(region.rows)
+                    .map(str::to_string),
+            );
+        }
+
+        let region = self.picker_region(rows, cols);
+        let screen = picker.render_to_string(region.rows, region.cols);
+        let picker_lines = screen.lines().chain(std::iter::repeat(""));
+        match (self.sidebar_region(rows, cols), &self.sidebar) {
+            (Some(sidebar_region), Some((_, position, render))) => {
+                let sidebar =
+                    render(sidebar_region.rows, sidebar_region.cols);
+                let sidebar_lines =
+                    sidebar.lines().chain(std::iter::repeat(""));
+                for (picker_line, sidebar_line) in
+                    picker_lines.zip(sidebar_lines).take(region.rows)
+                {
+                    let (left, left_cols, right) = match position {
+                        SidebarPosition::Left => {
+                            (sidebar_line, sidebar_region.cols, picker_line)
+                        }
+                        SidebarPosition::Right => {
+                            (picker_line, region.cols, sidebar_line)
+                        }
+                    };
+                    let padding = " "
+                        .repeat(left_cols.saturating_sub(ansi_width(left)));
+                    lines.push(format!("{left}{padding}{right}"));
+                }
+            }
+            _ => {
+                lines.extend(
+                    picker_lines.take(region.rows).map(str::to_string),
+                );
+            }
+        }
+
+        // trailing blank lines are left out, like the picker does
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    fn header_rows(&self, rows: usize) -> usize {
+        self.header
+            .as_ref()
+            .map_or(0, |(header_rows, _)| (*header_rows).min(rows))
+    }
+}
+
+/// Helpers for testing plugins which use a [`Picker`], without needing to
+/// run inside zellij.
+///
+/// Calls into zellij (such as subscribing to events or setting timers) do
+/// nothing when not compiled to wasm, so tests using these helpers should
+/// be run for the host target, for instance with `cargo test --target
+/// x86_64-unknown-linux-gnu`.
+///
+/// ```
+/// use zellij_nucleo::testing;
+/// use zellij_tile::prelude::*;
+///
+/// let mut picker = zellij_nucleo::Picker::default();
+/// picker.extend([
+///     zellij_nucleo::Entry::new("foo", 1),
+///     zellij_nucleo::Entry::new("bar", 2),
+/// ]);
+/// picker.enter_search_mode();
+/// testing::send(&mut picker, testing::text("ba"));
+/// let screen = testing::strip_ansi(&picker.render_to_string(3, 20));
+/// assert_eq!(screen.lines().nth(1), Some("> bar"));
+/// let responses = testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+/// assert!(matches!(
+///     responses[..],
+///     [zellij_nucleo::Response::Select { index: 1, .. }]
+/// ));
+/// ```
+pub mod testing {
+    use zellij_tile::prelude::*;
+
+    /// Returns the event for pressing the given key without any modifiers.
+    pub fn key(bare_key: BareKey) -> Event {
+        Event::Key(KeyWithModifier::new(bare_key))
+    }
+
+    /// Returns the event for pressing the given key while holding Ctrl.
+    pub fn ctrl(bare_key: BareKey) -> Event {
+        Event::Key(KeyWithModifier::new(bare_key).with_ctrl_modifier())
+    }
+
+    /// Returns the event for pressing the given key while holding Alt.
+    pub fn alt(bare_key: BareKey) -> Event {
+        Event::Key(KeyWithModifier::new(bare_key).with_alt_modifier())
+    }
+
+    /// Returns the events for typing the given text.
+    pub fn text(text: &str) -> Vec<Event> {
+        text.chars().map(|c| key(BareKey::Char(c))).collect()
+    }
+
+    /// Passes each of the given events to [`Picker::update`](
+    /// crate::Picker::update), returning the responses it produced.
+    pub fn send<T>(
+        picker: &mut crate::Picker<T>,
+        events: impl IntoIterator<Item = Event>,
+    ) -> Vec<crate::Response> {
+        events
+            .into_iter()
+            .filter_map(|event| picker.update(&event))
+            .collect()
+    }
+
+    /// Returns the strings of the current search results, in order.
+    pub fn results<T>(picker: &crate::Picker<T>) -> Vec<String> {
+        picker
+            .results()
+            .map(|result| result.entry.string.to_string())
+            .collect()
+    }
+
+    /// Returns the string of the selected entry.
+    pub fn selected<T>(picker: &crate::Picker<T>) -> Option<String> {
+        picker
+            .selected_result()
+            .map(|result| result.entry.string.to_string())
+    }
+
+    /// A [`Store`](crate::Store) which keeps everything in memory. Clones
+    /// share their contents, so a clone can be given to a picker while the
+    /// original is used to check what was saved, or to create another
+    /// picker which loads the same state.
+    #[derive(Debug, Clone, Default)]
+    pub struct MemoryStore(
+        std::rc::Rc<
+            std::cell::RefCell<std::collections::HashMap<String, Vec<u8>>>,
+        >,
+    );
+
+    impl MemoryStore {
+        /// Returns the blob saved under `key`, if any.
+        pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+            self.0.borrow().get(key).cloned()
+        }
+    }
+
+    impl crate::Store for MemoryStore {
+        fn load(&self, key: &str) -> std::io::Result<Option<Vec<u8>>> {
+            Ok(self.get(key))
+        }
+
+        fn save(&mut self, key: &str, data: &[u8]) -> std::io::Result<()> {
+            self.0.borrow_mut().insert(key.to_string(), data.to_vec());
+            Ok(())
+        }
+    }
+
+    /// Decodes the bytes read from a terminal in raw mode into key events,
+    /// for driving a picker from a real terminal outside of zellij (see the
+    /// `headless` example). This understands printable characters, control
+    /// characters, Alt combined with a character, and the common escape
+    /// sequences for arrow and navigation keys. Unrecognized escape
+    /// sequences are skipped. This is only available with the `headless`
+    /// feature.
+    #[cfg(feature = "headless")]
+    pub fn parse_terminal_input(input: &[u8]) -> Vec<Event> {
+        let input = String::from_utf8_lossy(input);
+        let mut events = vec![];
+        let mut chars = input.chars();
+        while let Some(c) = chars.next() {
+            let event = match c {
+                '\x1b' => match chars.next() {
+                    None => key(BareKey::Esc),
+                    Some('[' | 'O') => {
+                        let mut params = String::new();
+                        let mut last = None;
+                        for c in chars.by_ref() {
+                            if ('@'..='~').contains(&c) {
+                                last = Some(c);
+                                break;
+                            }
+                            params.push(c);
+                        }
+                        let bare_key = match (params.as_str(), last) {
+                            ("", Some('A')) => BareKey::Up,
+                            ("", Some('B')) => BareKey::Down,
+                            ("", Some('C')) => BareKey::Right,
+                            ("", Some('D')) => BareKey::Left,
+                            ("" | "1", Some('H'))
+                            | ("1" | "7", Some('~')) => BareKey::Home,
+                            ("" | "1", Some('F'))
+                            | ("4" | "8", Some('~')) => BareKey::End,
+                            ("3", Some('~')) => BareKey::Delete,
+                            ("5", Some('~')) => BareKey::PageUp,
+                            ("6", Some('~')) => BareKey::PageDown,
+                            _ => continue,
+                        };
+                        key(bare_key)
+                    }
+                    Some('\x1b') => {
+                        events.push(key(BareKey::Esc));
+                        key(BareKey::Esc)
+                    }
+                    Some(c) => Event::Key(
+                        KeyWithModifier::new(BareKey::Char(c))
+                            .with_alt_modifier(),
+                    ),
+                },
+                '\r' | '\n' => key(BareKey::Enter),
+                '\t' => key(BareKey::Tab),
+                '\x7f' | '\x08' => key(BareKey::Backspace),
+                '\x01'..='\x1a' => ctrl(BareKey::Char(char::from(
+                    b'a' + u8::try_from(c).unwrap() - 1,
+                ))),
+                c if c.is_control() => continue,
+                c => key(BareKey::Char(c)),
+            };
+            events.push(event);
+        }
+        events
+    }
+
+    /// Removes the ANSI escape sequences used for colors and other styling
+    /// from the output of [`Picker::render_to_string`](
+    /// crate::Picker::render_to_string).
+    pub fn strip_ansi(s: &str) -> String {
+        let mut stripped = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                if chars.next() == Some('[') {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+            } else {
+                stripped.push(c);
+            }
+        }
+        stripped
     }
 }
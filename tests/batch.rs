@@ -0,0 +1,179 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn entries(strings: &[&'static str]) -> Vec<zellij_nucleo::Entry<u32>> {
+    strings
+        .iter()
+        .zip(0..)
+        .map(|(string, data)| zellij_nucleo::Entry::new(*string, data))
+        .collect()
+}
+
+#[test]
+fn test_batch_searches_once() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["foo", "bar", "baz"]));
+    assert!(picker.select_entry(2));
+    assert_eq!(testing::selected(&picker).as_deref(), Some("baz"));
+
+    let searches = picker.metrics().searches;
+    picker.batch(|picker| {
+        picker.clear();
+        assert!(picker.selected_result().is_none());
+        picker.extend(entries(&["baz", "quux"]));
+        picker.extend(entries(&["foo"]));
+    });
+    assert_eq!(picker.metrics().searches, searches + 1);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("baz"));
+    assert_eq!(picker.results().count(), 3);
+}
+
+#[test]
+fn test_batch_clear_remembers_selection() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["foo", "bar", "baz"]));
+    assert!(picker.select_entry(0));
+
+    picker.batch(|picker| picker.clear());
+    assert!(picker.selected_result().is_none());
+
+    picker.extend(entries(&["bar", "baz", "foo"]));
+    assert_eq!(testing::selected(&picker).as_deref(), Some("foo"));
+}
+
+#[test]
+fn test_batch_removed_selection() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["foo", "bar", "baz"]));
+    assert!(picker.select_entry(2));
+
+    picker.batch(|picker| {
+        picker.clear();
+        picker.extend(entries(&["foo", "bar"]));
+    });
+    assert_eq!(testing::selected(&picker).as_deref(), Some("bar"));
+}
+
+#[test]
+fn test_batch_query() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["foo", "bar"]));
+
+    let searches = picker.metrics().searches;
+    picker.batch(|picker| {
+        picker.set_query("ba");
+        picker.clear();
+        picker.extend(entries(&["foo", "bar", "baz"]));
+    });
+    assert_eq!(picker.metrics().searches, searches + 1);
+    let results: Vec<_> = picker
+        .results()
+        .map(|result| result.entry.string.to_string())
+        .collect();
+    assert_eq!(results, ["bar", "baz"]);
+}
+
+struct Refresh;
+
+impl zellij_nucleo::CustomMode<u32> for Refresh {
+    fn prompt(&self, _: &zellij_nucleo::Picker<u32>) -> String {
+        "refresh".to_string()
+    }
+
+    fn handle_key(
+        &mut self,
+        picker: &mut zellij_nucleo::Picker<u32>,
+        key: &KeyWithModifier,
+    ) -> Option<zellij_nucleo::Response> {
+        if key.bare_key == BareKey::Char('r') {
+            picker.clear();
+            picker.extend(entries(&["foo"]));
+        }
+        None
+    }
+}
+
+#[test]
+fn test_custom_mode_modifies_entries() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.add_mode("refresh", Refresh);
+    picker.extend(entries(&["foo", "bar", "baz", "quux"]));
+    picker.render_to_string(10, 20);
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("quux"));
+
+    assert!(picker.enter_mode("refresh"));
+    testing::send(&mut picker, testing::text("r"));
+    assert_eq!(testing::selected(&picker).as_deref(), Some("foo"));
+    let screen = testing::strip_ansi(&picker.render_to_string(10, 20));
+    assert_eq!(screen.lines().nth(1), Some("> foo"));
+
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 0, rank: 0 }]
+    ));
+}
+
+#[test]
+fn test_changes_after_update_are_batched() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend(entries(&["foo", "bar", "baz"]));
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("bar"));
+
+    // like the plugin in the readme, replacing the entries while handling
+    // an event
+    let searches = picker.metrics().searches;
+    picker.clear();
+    picker.extend(entries(&["quux", "bar"]));
+    picker.extend(entries(&["foo"]));
+    assert!(picker.selected_result().is_none());
+    assert!(picker.needs_redraw());
+
+    picker.render_to_string(10, 20);
+    assert_eq!(picker.metrics().searches, searches + 1);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("bar"));
+    assert_eq!(picker.results().count(), 3);
+}
+
+#[test]
+fn test_query_change_after_update_is_batched() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend(entries(&["foo", "bar", "baz"]));
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("baz"));
+
+    let searches = picker.metrics().searches;
+    picker.clear();
+    picker.set_query("ba");
+    picker.extend(entries(&["bar", "foo"]));
+    picker.flush();
+    assert_eq!(picker.metrics().searches, searches + 1);
+    // the selected entry is gone, so the first match is selected
+    assert_eq!(testing::selected(&picker).as_deref(), Some("bar"));
+    assert_eq!(picker.results().count(), 1);
+}
+
+#[test]
+fn test_next_event_applies_batched_changes() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend(entries(&["foo", "bar"]));
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+
+    picker.clear();
+    picker.extend(entries(&["bar", "baz", "foo"]));
+    // the key moves the selection within the new entries, starting from
+    // the restored selection
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("baz"));
+
+    // changes made outside of update aren't deferred
+    picker.render_to_string(10, 20);
+    picker.extend(entries(&["quux"]));
+    assert_eq!(picker.results().count(), 4);
+}
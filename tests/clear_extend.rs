@@ -0,0 +1,141 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn selected(picker: &mut zellij_nucleo::Picker<u32>) -> Option<u32> {
+    match picker.update(&testing::key(BareKey::Enter)) {
+        Some(zellij_nucleo::Response::Select { index, .. }) => {
+            Some(picker.entries()[index].data)
+        }
+        _ => None,
+    }
+}
+
+fn entries(
+    strings: &[&'static str],
+    start: u32,
+) -> Vec<zellij_nucleo::Entry<u32>> {
+    strings
+        .iter()
+        .zip(start..)
+        .map(|(string, data)| zellij_nucleo::Entry::new(*string, data))
+        .collect()
+}
+
+#[test]
+fn test_clear_extend_preserves_selection() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["a", "b", "c"], 0));
+    picker.update(&testing::key(BareKey::Down));
+    assert_eq!(selected(&mut picker), Some(1));
+
+    picker.clear();
+    picker.extend(entries(&["c", "b", "a"], 10));
+    assert_eq!(selected(&mut picker), Some(11));
+
+    picker.clear();
+    picker.extend(entries(&["a", "c"], 20));
+    assert_eq!(selected(&mut picker), Some(20));
+}
+
+#[test]
+fn test_clear_extend_with_entry_key() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_entry_key(|entry| entry.data.to_string());
+    picker.extend(entries(&["a", "b", "c"], 0));
+    picker.update(&testing::key(BareKey::Down));
+    picker.update(&testing::key(BareKey::Down));
+
+    picker.clear();
+    picker.extend(entries(&["x", "y", "z"], 0));
+    assert_eq!(selected(&mut picker), Some(2));
+}
+
+#[test]
+fn test_clear_extend_does_not_report_selection_change() {
+    let changes = std::rc::Rc::new(std::cell::Cell::new(0));
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.on_after_selection_change({
+        let changes = changes.clone();
+        move |_| changes.set(changes.get() + 1)
+    });
+    picker.extend(entries(&["a", "b"], 0));
+    assert_eq!(changes.get(), 1);
+
+    picker.clear();
+    picker.extend(entries(&["b", "a"], 0));
+    assert_eq!(changes.get(), 1);
+}
+
+#[test]
+fn test_refresh_preserves_query_and_selection() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend(entries(&["ab", "ac", "b"], 0));
+    picker.set_query("a");
+    picker.update(&testing::key(BareKey::Down));
+
+    let response = picker.update(&Event::Key(
+        KeyWithModifier::new(BareKey::Char('r')).with_ctrl_modifier(),
+    ));
+    assert!(matches!(response, Some(zellij_nucleo::Response::Refresh)));
+    assert!(picker.is_searching());
+
+    picker.clear();
+    picker.extend(entries(&["b", "ac", "ab", "ad"], 10));
+    assert!(!picker.is_searching());
+    assert_eq!(picker.query(), "a");
+    assert_eq!(selected(&mut picker), Some(11));
+}
+
+#[test]
+fn test_clear_extend_preserves_marks() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.enable_multi_select();
+    picker.extend(entries(&["a", "b", "c"], 0));
+    picker.update(&testing::key(BareKey::Down));
+    picker.update(&testing::key(BareKey::Tab));
+    picker.update(&testing::key(BareKey::Tab));
+    assert_eq!(picker.marked(), [1, 2]);
+
+    picker.clear();
+    picker.extend(entries(&["c", "a", "b"], 10));
+    assert_eq!(picker.marked(), [2, 0]);
+}
+
+#[test]
+fn test_clear_extend_preserves_pins() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend(entries(&["a", "b", "c"], 0));
+    picker.pin("c");
+    assert_eq!(testing::results(&picker), ["c", "a", "b"]);
+
+    picker.clear();
+    picker.extend(entries(&["b", "c", "a"], 10));
+    assert_eq!(testing::results(&picker), ["c", "b", "a"]);
+}
+
+#[test]
+fn test_clear_extend_preserves_scroll_position() {
+    let entries = || {
+        (0..20).map(|i| zellij_nucleo::Entry::new(format!("entry{i:02}"), i))
+    };
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend(entries());
+    for _ in 0..10 {
+        picker.update(&testing::key(BareKey::Down));
+    }
+    picker.scroll_window(5);
+    // move back up a bit, so that the window no longer follows the
+    // selection
+    picker.update(&testing::key(BareKey::Up));
+    picker.update(&testing::key(BareKey::Up));
+    let window = picker.scroll_window(5);
+    assert_eq!(window, 6..11);
+    let screen = picker.render_to_string(8, 20);
+
+    picker.clear();
+    picker.extend(entries());
+    assert_eq!(picker.scroll_window(5), window);
+    assert_eq!(picker.render_to_string(8, 20), screen);
+}
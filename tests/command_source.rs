@@ -0,0 +1,69 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn command_result(generation: &str, stdout: &str) -> Event {
+    Event::RunCommandResult(
+        Some(0),
+        stdout.as_bytes().to_vec(),
+        vec![],
+        [("zellij_nucleo_command".to_string(), generation.to_string())]
+            .into(),
+    )
+}
+
+fn results(picker: &zellij_nucleo::Picker<String>) -> Vec<String> {
+    picker
+        .results()
+        .map(|result| result.entry.data.clone())
+        .collect()
+}
+
+#[test]
+fn test_command_source() {
+    let mut picker = zellij_nucleo::Picker::<String>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.set_command_source(["rg", "{q}"], std::time::Duration::ZERO);
+    assert!(picker.is_searching());
+    testing::send(&mut picker, [command_result("1", "zzz\nyyy\n")]);
+    assert!(!picker.is_searching());
+    assert_eq!(results(&picker), ["zzz", "yyy"]);
+
+    // the output isn't filtered by the query
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("ab"));
+    assert!(picker.is_searching());
+    assert_eq!(results(&picker), ["zzz", "yyy"]);
+
+    // output from commands run for an older query is ignored
+    testing::send(&mut picker, [Event::Timer(0.0)]);
+    testing::send(&mut picker, [command_result("1", "old\n")]);
+    assert!(picker.is_searching());
+    assert_eq!(results(&picker), ["zzz", "yyy"]);
+
+    testing::send(&mut picker, [command_result("2", "foo\nbar\n")]);
+    assert!(!picker.is_searching());
+    assert_eq!(results(&picker), ["foo", "bar"]);
+
+    picker.clear_command_source();
+    assert!(results(&picker).is_empty());
+}
+
+#[test]
+fn test_command_source_permission_denied() {
+    let mut picker = zellij_nucleo::Picker::<String>::default();
+    picker.set_command_source(["rg", "{q}"], std::time::Duration::ZERO);
+    let responses = testing::send(
+        &mut picker,
+        [Event::PermissionRequestResult(PermissionStatus::Denied)],
+    );
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::PermissionDenied]
+    ));
+    assert!(picker.permission_denied());
+    let screen = testing::strip_ansi(&picker.render_to_string(3, 60));
+    assert_eq!(
+        screen.lines().last(),
+        Some("(permission denied: this picker can't load its entries)")
+    );
+}
@@ -0,0 +1,172 @@
+#[test]
+fn test_describe_config() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.configure(zellij_nucleo::PickerConfig {
+        match_mode: Some(zellij_nucleo::MatchMode::Substring),
+        theme: Some(zellij_nucleo::Theme::monochrome()),
+        result_limit: Some(Some(50)),
+        ..Default::default()
+    });
+    let options = picker.describe_config();
+    let value = |name: &str| {
+        options
+            .iter()
+            .find(|option| option.name == name)
+            .and_then(|option| option.value.as_deref())
+    };
+    assert_eq!(value("nucleo_match_mode"), Some("substring"));
+    assert_eq!(value("nucleo_theme"), Some("monochrome"));
+    assert_eq!(value("nucleo_result_limit"), Some("50"));
+    assert_eq!(value("nucleo_prefix_first"), Some("false"));
+    assert_eq!(value("nucleo_pipe_name"), None);
+
+    // the current values are all accepted as configuration
+    let configuration = options
+        .iter()
+        .filter_map(|option| {
+            Some((option.name.clone(), option.value.clone()?))
+        })
+        .collect();
+    assert!(zellij_nucleo::PickerConfig::parse(&configuration).is_ok());
+
+    picker.set_theme(zellij_nucleo::Theme {
+        hint: zellij_nucleo::TextStyle::fg(zellij_nucleo::Color::Red),
+        ..zellij_nucleo::Theme::default()
+    });
+    let option = picker
+        .describe_config()
+        .into_iter()
+        .find(|option| option.name == "nucleo_theme")
+        .unwrap();
+    assert_eq!(option.value, None);
+    assert_eq!(
+        option.expected,
+        "'default', 'high_contrast', 'deuteranopia', 'monochrome'"
+    );
+}
+
+fn configuration(
+    options: &[(&str, &str)],
+) -> std::collections::BTreeMap<String, String> {
+    options
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |options| {
+        zellij_nucleo::PickerConfig::parse(&configuration(options))
+            .unwrap_err()
+    };
+
+    let err = error(&[("nucleo_match_mode", "fuzzzy")]);
+    assert_eq!(err.option, "nucleo_match_mode");
+    assert_eq!(err.value, "fuzzzy");
+    assert_eq!(
+        err.expected,
+        "'fuzzy', 'substring', 'prefix', 'suffix', 'exact'"
+    );
+
+    let err = error(&[("nucleo_scrolloff", "-1")]);
+    assert_eq!(err.option, "nucleo_scrolloff");
+    assert_eq!(err.value, "-1");
+    assert_eq!(err.expected, "a number");
+
+    let err = error(&[("nucleo_prefix_first", "yes")]);
+    assert_eq!(
+        err.to_string(),
+        "unrecognized value yes for option 'nucleo_prefix_first': \
+         expected 'true', 'false'"
+    );
+
+    // options without the nucleo_ prefix belong to the plugin itself
+    assert!(zellij_nucleo::PickerConfig::parse(&configuration(&[(
+        "match_mode",
+        "fuzzzy"
+    )]))
+    .is_ok());
+}
+
+#[test]
+fn test_try_load_applies_nothing_on_error() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    let err = picker
+        .try_load(&configuration(&[
+            ("nucleo_match_mode", "substring"),
+            ("nucleo_prefix_first", "true"),
+            ("nucleo_scrolloff", "lots"),
+        ]))
+        .unwrap_err();
+    assert_eq!(err.option, "nucleo_scrolloff");
+
+    let options = picker.describe_config();
+    let value = |name: &str| {
+        options
+            .iter()
+            .find(|option| option.name == name)
+            .and_then(|option| option.value.clone())
+    };
+    assert_eq!(value("nucleo_match_mode").as_deref(), Some("fuzzy"));
+    assert_eq!(value("nucleo_prefix_first").as_deref(), Some("false"));
+}
+
+#[test]
+fn test_describe_config_reports_configured_values() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.configure(zellij_nucleo::PickerConfig {
+        match_paths: Some(true),
+        start_in_search_mode: Some(true),
+        ..Default::default()
+    });
+    // leaving search mode doesn't change how the picker starts
+    picker.enter_normal_mode();
+    let value = |picker: &zellij_nucleo::Picker<u32>, name: &str| {
+        picker
+            .describe_config()
+            .into_iter()
+            .find(|option| option.name == name)
+            .and_then(|option| option.value)
+    };
+    assert_eq!(
+        value(&picker, "nucleo_match_paths").as_deref(),
+        Some("true")
+    );
+    assert_eq!(
+        value(&picker, "nucleo_start_in_search_mode").as_deref(),
+        Some("true")
+    );
+
+    picker.clear_match_paths();
+    assert_eq!(
+        value(&picker, "nucleo_match_paths").as_deref(),
+        Some("false")
+    );
+}
+
+#[test]
+fn test_describe_config_round_trips() {
+    let configuration = configuration(&[
+        ("nucleo_case_matching", "ignore"),
+        ("nucleo_match_paths", "true"),
+        ("nucleo_start_in_search_mode", "true"),
+        ("nucleo_keybind_last", "Ctrl j, Alt g"),
+        ("nucleo_highlight_new", "500"),
+        ("nucleo_search_chunk_size", "none"),
+        ("nucleo_placeholder", "type to search"),
+        ("nucleo_pipe_name", "entries"),
+    ]);
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.configure(
+        zellij_nucleo::PickerConfig::parse(&configuration).unwrap(),
+    );
+    let described: std::collections::BTreeMap<_, _> = picker
+        .describe_config()
+        .into_iter()
+        .filter_map(|option| Some((option.name, option.value?)))
+        .collect();
+    for (name, value) in &configuration {
+        assert_eq!(described.get(name), Some(value), "{name}");
+    }
+}
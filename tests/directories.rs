@@ -0,0 +1,119 @@
+#![cfg(feature = "helpers")]
+
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn pipe_message(payload: &str) -> PipeMessage {
+    PipeMessage {
+        source: PipeSource::Keybind,
+        name: "recent_directories".to_string(),
+        payload: Some(payload.to_string()),
+        args: Default::default(),
+        is_private: false,
+    }
+}
+
+#[test]
+fn test_recent_directories() {
+    let store = testing::MemoryStore::default();
+    let mut directories = zellij_nucleo::DirectoryPicker::default();
+    directories.set_store(store.clone());
+    directories
+        .picker_mut()
+        .set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+
+    assert!(directories.pipe(&pipe_message("/src/zellij")));
+    assert!(directories.pipe(&pipe_message("/tmp\n/home/user")));
+    directories.add("/src/zellij");
+    assert_eq!(
+        directories.directories(),
+        ["/src/zellij", "/tmp", "/home/user"]
+    );
+
+    testing::send(directories.picker_mut(), [testing::key(BareKey::Down)]);
+    let response = directories.update(&testing::key(BareKey::Enter));
+    let Some(zellij_nucleo::Response::Select { index, .. }) = response else {
+        panic!("expected a selection, got {response:?}");
+    };
+    assert_eq!(directories.picker_mut().entries()[index].data, "/tmp");
+    assert_eq!(
+        directories.directories(),
+        ["/tmp", "/src/zellij", "/home/user"]
+    );
+
+    let mut reloaded = zellij_nucleo::DirectoryPicker::default();
+    reloaded.set_store(store);
+    assert_eq!(
+        reloaded.directories(),
+        ["/tmp", "/src/zellij", "/home/user"]
+    );
+}
+
+#[test]
+fn test_bookmarks() {
+    let store = testing::MemoryStore::default();
+    let mut directories = zellij_nucleo::DirectoryPicker::default();
+    directories.set_store(store.clone());
+    directories
+        .picker_mut()
+        .set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    directories.pipe(&pipe_message("/tmp\n/home/user\n/src/zellij"));
+    assert_eq!(
+        testing::results(directories.picker_mut()),
+        ["/tmp", "/home/user", "/src/zellij"]
+    );
+
+    // Alt-m bookmarks the selected directory
+    testing::send(directories.picker_mut(), [testing::key(BareKey::End)]);
+    let response = directories.update(&testing::alt(BareKey::Char('m')));
+    assert!(response.is_none(), "{response:?}");
+    directories.bookmark("/etc");
+    assert_eq!(directories.bookmarks(), ["/src/zellij", "/etc"]);
+    // the list was replaced while handling the key, so it is only
+    // searched again before the next render
+    directories.picker_mut().flush();
+    assert_eq!(
+        testing::results(directories.picker_mut()),
+        ["/src/zellij", "/etc", "/tmp", "/home/user"]
+    );
+    let screen = testing::strip_ansi(
+        &directories.picker_mut().render_to_string(9, 20),
+    );
+    let lines: Vec<_> = screen.lines().map(str::trim_end).collect();
+    assert_eq!(
+        lines[1..7],
+        [
+            "Bookmarks",
+            "> /src/zellij",
+            "  /etc",
+            "Recent",
+            "  /tmp",
+            "  /home/user",
+        ]
+    );
+
+    // choosing a bookmark keeps it in place
+    testing::send(directories.picker_mut(), [testing::key(BareKey::Down)]);
+    let response = directories.update(&testing::key(BareKey::Enter));
+    let Some(zellij_nucleo::Response::Select { index, .. }) = response else {
+        panic!("expected a selection, got {response:?}");
+    };
+    assert_eq!(directories.picker_mut().entries()[index].data, "/etc");
+    assert_eq!(
+        directories.directories(),
+        ["/etc", "/tmp", "/home/user", "/src/zellij"]
+    );
+
+    let mut reloaded = zellij_nucleo::DirectoryPicker::default();
+    reloaded.set_store(store.clone());
+    reloaded
+        .picker_mut()
+        .set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    assert_eq!(reloaded.bookmarks(), ["/src/zellij", "/etc"]);
+    reloaded.unbookmark("/src/zellij");
+    assert_eq!(
+        testing::results(reloaded.picker_mut()),
+        ["/etc", "/tmp", "/home/user", "/src/zellij"]
+    );
+    assert_eq!(store.get("bookmarks").as_deref(), Some(&b"/etc"[..]));
+}
@@ -0,0 +1,50 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn new_picker(
+    empty_select: zellij_nucleo::EmptySelect,
+) -> zellij_nucleo::Picker<u32> {
+    let mut picker = zellij_nucleo::Picker::default();
+    picker.set_empty_select(empty_select);
+    picker
+}
+
+#[test]
+fn test_enter_with_no_entries() {
+    let mut picker = new_picker(zellij_nucleo::EmptySelect::Ignore);
+    assert!(picker.update(&testing::key(BareKey::Enter)).is_none());
+
+    let mut picker = new_picker(zellij_nucleo::EmptySelect::Cancel);
+    assert!(matches!(
+        picker.update(&testing::key(BareKey::Enter)),
+        Some(zellij_nucleo::Response::Cancel)
+    ));
+
+    let mut picker = new_picker(zellij_nucleo::EmptySelect::Query);
+    assert!(matches!(
+        picker.update(&testing::key(BareKey::Enter)),
+        Some(zellij_nucleo::Response::Query(query)) if query.is_empty()
+    ));
+}
+
+#[test]
+fn test_enter_with_no_matches() {
+    let mut picker = new_picker(zellij_nucleo::EmptySelect::Query);
+    picker.extend([zellij_nucleo::Entry::new("foo", 1)]);
+    picker.enter_search_mode();
+    for c in "bar".chars() {
+        picker.update(&testing::key(BareKey::Char(c)));
+    }
+    assert!(matches!(
+        picker.update(&testing::key(BareKey::Enter)),
+        Some(zellij_nucleo::Response::Query(query)) if query == "bar"
+    ));
+
+    picker.update(&testing::key(BareKey::Backspace));
+    picker.update(&testing::key(BareKey::Backspace));
+    picker.update(&testing::key(BareKey::Backspace));
+    assert!(matches!(
+        picker.update(&testing::key(BareKey::Enter)),
+        Some(zellij_nucleo::Response::Select { index: 0, rank: 0 })
+    ));
+}
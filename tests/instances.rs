@@ -0,0 +1,116 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_focused() {
+    let mut files = zellij_nucleo::Picker::<u32>::default();
+    let mut commands = zellij_nucleo::Picker::<u32>::default();
+    files.extend([zellij_nucleo::Entry::new("foo.rs", 1)]);
+    commands.extend([zellij_nucleo::Entry::new("format", 2)]);
+    files.enter_search_mode();
+    commands.enter_search_mode();
+    commands.set_focused(false);
+    assert!(files.is_focused());
+    assert!(!commands.is_focused());
+
+    for event in testing::text("fo") {
+        testing::send(&mut files, [event.clone()]);
+        testing::send(&mut commands, [event]);
+    }
+    assert_eq!(files.query(), "fo");
+    assert_eq!(commands.query(), "");
+
+    files.set_focused(false);
+    commands.set_focused(true);
+    let event = testing::key(BareKey::Enter);
+    assert!(testing::send(&mut files, [event.clone()]).is_empty());
+    assert!(matches!(
+        testing::send(&mut commands, [event])[..],
+        [zellij_nucleo::Response::Select { index: 0, rank: 0 }]
+    ));
+}
+
+#[test]
+fn test_config_namespace() {
+    let configuration = [
+        ("nucleo_match_mode", "substring"),
+        ("nucleo_theme", "monochrome"),
+        ("files_nucleo_match_paths", "true"),
+        ("commands_nucleo_match_mode", "prefix"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+
+    let files = zellij_nucleo::PickerConfig::parse_namespaced(
+        &configuration,
+        "files",
+    )
+    .unwrap();
+    assert_eq!(files.match_mode, Some(zellij_nucleo::MatchMode::Substring));
+    assert_eq!(files.match_paths, Some(true));
+    assert_eq!(files.theme, Some(zellij_nucleo::Theme::monochrome()));
+
+    let commands = zellij_nucleo::PickerConfig::parse_namespaced(
+        &configuration,
+        "commands",
+    )
+    .unwrap();
+    assert_eq!(commands.match_mode, Some(zellij_nucleo::MatchMode::Prefix));
+    assert_eq!(commands.match_paths, None);
+
+    let mut configuration = configuration;
+    configuration
+        .insert("commands_nucleo_scrolloff".to_string(), "lots".to_string());
+    let e = zellij_nucleo::PickerConfig::parse_namespaced(
+        &configuration,
+        "commands",
+    )
+    .unwrap_err();
+    assert_eq!(e.option, "commands_nucleo_scrolloff");
+}
+
+#[cfg(feature = "mouse")]
+#[test]
+fn test_unfocused_ignores_mouse() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+    ]);
+    picker.enable_mouse();
+    picker.render_to_string(5, 20);
+    picker.set_focused(false);
+    testing::send(&mut picker, [Event::Mouse(Mouse::ScrollDown(1))]);
+    assert_eq!(picker.selected_result().unwrap().entry.string, "foo");
+
+    picker.set_focused(true);
+    testing::send(&mut picker, [Event::Mouse(Mouse::ScrollDown(1))]);
+    assert_eq!(picker.selected_result().unwrap().entry.string, "bar");
+}
+
+#[test]
+fn test_namespaced_pipe_name() {
+    let configuration = [
+        ("nucleo_pipe_name", "picker"),
+        ("commands_nucleo_pipe_name", "commands"),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .collect();
+
+    let files = zellij_nucleo::PickerConfig::parse_namespaced(
+        &configuration,
+        "files",
+    )
+    .unwrap();
+    assert_eq!(files.pipe_name.as_deref(), Some("files_picker"));
+
+    let commands = zellij_nucleo::PickerConfig::parse_namespaced(
+        &configuration,
+        "commands",
+    )
+    .unwrap();
+    assert_eq!(commands.pipe_name.as_deref(), Some("commands"));
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "json")]
+
+use zellij_tile::prelude::*;
+
+fn pipe_message(payload: &str) -> PipeMessage {
+    PipeMessage {
+        source: PipeSource::Keybind,
+        name: "entries".to_string(),
+        payload: Some(payload.to_string()),
+        args: [("format".to_string(), "json".to_string())]
+            .into_iter()
+            .collect(),
+        is_private: false,
+    }
+}
+
+fn picker() -> zellij_nucleo::Picker<String> {
+    let mut picker = zellij_nucleo::Picker::<String>::default();
+    picker.set_pipe_name("entries");
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker
+}
+
+fn entries(picker: &zellij_nucleo::Picker<String>) -> Vec<(String, String)> {
+    picker
+        .results()
+        .map(|result| {
+            (result.entry.string.to_string(), result.entry.data.clone())
+        })
+        .collect()
+}
+
+#[test]
+fn test_json_entries() {
+    let mut picker = picker();
+    assert!(picker.handle_pipe(&pipe_message(
+        r#"[
+            "apple",
+            {"text": "banana", "data": "yellow", "group": "fruit"},
+            {"text": "cherry", "current": true, "always_show": true}
+        ]"#
+    )));
+    let mut all = entries(&picker);
+    all.sort();
+    assert_eq!(
+        all,
+        [
+            ("apple".to_string(), "apple".to_string()),
+            ("banana".to_string(), "yellow".to_string()),
+            ("cherry".to_string(), "cherry".to_string()),
+        ]
+    );
+    let entry = |string: &str| {
+        picker
+            .results()
+            .find(|result| result.entry.string == string)
+            .unwrap()
+            .entry
+    };
+    assert_eq!(entry("banana").group.as_deref(), Some("fruit"));
+    assert!(!entry("banana").current);
+    assert!(entry("cherry").current);
+    assert!(entry("cherry").always_show);
+}
+
+#[test]
+fn test_json_sources() {
+    let mut picker = picker();
+    picker.handle_pipe(&pipe_message(
+        r#"{"action": "add", "source": "a", "entries": ["one", "two"]}"#,
+    ));
+    picker.handle_pipe(&pipe_message(
+        r#"{"action": "add", "source": "b", "entries": ["three"]}"#,
+    ));
+    picker.handle_pipe(&pipe_message(
+        r#"{"action": "remove", "source": "a", "entries": ["one"]}"#,
+    ));
+    let strings = |picker: &zellij_nucleo::Picker<String>| {
+        entries(picker)
+            .into_iter()
+            .map(|(string, _)| string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(strings(&picker), ["two", "three"]);
+    picker.handle_pipe(&pipe_message(
+        r#"{"action": "replace", "source": "b", "entries": ["four"]}"#,
+    ));
+    assert_eq!(strings(&picker), ["two", "four"]);
+    picker
+        .handle_pipe(&pipe_message(r#"{"action": "clear", "source": "a"}"#));
+    assert_eq!(strings(&picker), ["four"]);
+}
+
+#[test]
+fn test_json_invalid_payloads() {
+    let mut picker = picker();
+    picker.handle_pipe(&pipe_message(r#"["apple"]"#));
+    for payload in [
+        "not json",
+        "42",
+        r#"["banana", 42]"#,
+        r#"[{"data": "no text"}]"#,
+        r#"[{"text": "banana", "current": "yes"}]"#,
+        r#"{"source": "a", "entries": ["banana"]}"#,
+        r#"{"action": "add", "entries": ["banana"]}"#,
+        r#"{"action": "rename", "source": "a", "entries": ["banana"]}"#,
+        r#"{"action": "remove", "source": "a", "entries": [{"text": "x"}]}"#,
+    ] {
+        // the message is still meant for the picker, it just does nothing
+        assert!(picker.handle_pipe(&pipe_message(payload)), "{payload}");
+        assert_eq!(
+            entries(&picker),
+            [("apple".to_string(), "apple".to_string())],
+            "{payload}"
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_jumplist() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+        zellij_nucleo::Entry::new("date", 4),
+    ]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("date"));
+
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("apple"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("apple"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('i'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+    testing::send(&mut picker, [testing::alt(BareKey::Char('i'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("date"));
+    testing::send(&mut picker, [testing::alt(BareKey::Char('i'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("date"));
+}
+
+#[test]
+fn test_jumplist_ignores_adjacent_moves() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+        zellij_nucleo::Entry::new("date", 4),
+    ]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("apple"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('i'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+
+    // jumping back from somewhere which wasn't jumped to remembers it, so
+    // that it can be jumped forward to again
+    testing::send(&mut picker, [testing::key(BareKey::Up)]);
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('i'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+}
+
+#[test]
+fn test_jumplist_skips_non_matching() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+        zellij_nucleo::Entry::new("date", 4),
+    ]);
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    testing::send(&mut picker, [testing::key(BareKey::Up)]);
+    testing::send(&mut picker, [testing::key(BareKey::Home)]);
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("a"));
+    assert_eq!(testing::selected(&picker).as_deref(), Some("apple"));
+
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('o'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("date"));
+}
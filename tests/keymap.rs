@@ -0,0 +1,75 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_keymap_keeps_user_bindings() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+    ]);
+    picker.bind(
+        KeyWithModifier::new(BareKey::Char('j')).with_ctrl_modifier(),
+        zellij_nucleo::Action::Last,
+    );
+    picker.unbind(
+        KeyWithModifier::new(BareKey::Char('p')).with_ctrl_modifier(),
+    );
+    picker.set_keymap(zellij_nucleo::Keymap::Fzf);
+
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('n'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('p'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('j'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('k'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+
+    // switching presets drops the old preset's bindings, but not the
+    // user's
+    picker.set_keymap(zellij_nucleo::Keymap::Emacs);
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('k'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('j'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('p'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+}
+
+#[test]
+fn test_help_lists_bindings() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([zellij_nucleo::Entry::new("apple", 1)]);
+    picker.bind(
+        KeyWithModifier::new(BareKey::Char('j')).with_ctrl_modifier(),
+        zellij_nucleo::Action::Last,
+    );
+    picker.unbind(
+        KeyWithModifier::new(BareKey::Char('a')).with_ctrl_modifier(),
+    );
+    picker.set_keymap(zellij_nucleo::Keymap::Fzf);
+    testing::send(&mut picker, [testing::key(BareKey::F(1))]);
+    let screen = testing::strip_ansi(&picker.render_to_string(60, 80));
+    let line = |description: &str| {
+        screen
+            .lines()
+            .find(|line| line.trim_end().ends_with(description))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .unwrap_or_else(|| panic!("no help line for {description:?}"))
+    };
+
+    assert_eq!(line("select the last entry")[..2], ["C-j", "select"]);
+    assert_eq!(
+        line("select next entry")[..4],
+        ["C-n,", "Down,", "Tab", "select"]
+    );
+    assert!(line("cancel").starts_with(&["C-g,", "Esc,", "C-c"]));
+    // Home is only bound in normal mode, and C-a was unbound
+    assert_eq!(
+        line("move to the start of the query")[..2],
+        ["Home", "move"]
+    );
+}
@@ -0,0 +1,49 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn picker() -> zellij_nucleo::Picker<u32> {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+        zellij_nucleo::Entry::new("date", 4),
+    ]);
+    picker
+}
+
+#[test]
+fn test_macros_disabled_by_default() {
+    let mut picker = picker();
+    testing::send(
+        &mut picker,
+        [
+            testing::key(BareKey::Char('q')),
+            testing::key(BareKey::Down),
+            testing::key(BareKey::Char('q')),
+        ],
+    );
+    assert!(picker.recorded_macro().is_empty());
+    testing::send(&mut picker, [testing::key(BareKey::Char('@'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("banana"));
+}
+
+#[test]
+fn test_macros() {
+    let mut picker = picker();
+    picker.set_macros(true);
+    testing::send(
+        &mut picker,
+        [
+            testing::key(BareKey::Char('q')),
+            testing::key(BareKey::Down),
+            testing::key(BareKey::Char('q')),
+        ],
+    );
+    assert_eq!(
+        picker.recorded_macro(),
+        [KeyWithModifier::new(BareKey::Down)]
+    );
+    testing::send(&mut picker, [testing::key(BareKey::Char('@'))]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("cherry"));
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "mouse")]
+
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn entries(strings: &[&'static str]) -> Vec<zellij_nucleo::Entry<u32>> {
+    strings
+        .iter()
+        .zip(0..)
+        .map(|(string, data)| zellij_nucleo::Entry::new(*string, data))
+        .collect()
+}
+
+#[test]
+fn test_drag_after_results_shrink() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.enable_multi_select();
+    picker.enable_mouse();
+    picker.extend(entries(&["a", "b", "c", "d", "e"]));
+    let screen = testing::strip_ansi(&picker.render_to_string(8, 20));
+    let line = |string: &str| {
+        screen
+            .lines()
+            .position(|line| line.trim_end().ends_with(string))
+            .map(|line| isize::try_from(line).unwrap())
+            .unwrap()
+    };
+    let (line_b, line_d, line_e) = (line(" b"), line(" d"), line(" e"));
+
+    // the plugin replaces the entries while handling the click, so they
+    // are only searched again when the next event arrives
+    testing::send(&mut picker, [Event::Mouse(Mouse::LeftClick(line_e, 4))]);
+    picker.clear();
+    picker.extend(entries(&["a", "b"]));
+
+    // the rows below the remaining results no longer hold entries
+    testing::send(&mut picker, [Event::Mouse(Mouse::Hold(line_d, 4))]);
+    assert!(picker.marked().is_empty());
+    testing::send(&mut picker, [Event::Mouse(Mouse::Hold(line_b, 4))]);
+    assert_eq!(picker.marked(), [1]);
+    assert_eq!(testing::selected(&picker).as_deref(), Some("b"));
+}
@@ -0,0 +1,62 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_prompt_number() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+    ]);
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    let [zellij_nucleo::Response::Select { index, .. }] = responses[..]
+    else {
+        panic!("unexpected responses {responses:?}");
+    };
+
+    picker.prompt_number(index, "How many lines?", 1..=100);
+    assert!(picker.is_prompting());
+    let responses = testing::send(&mut picker, testing::text("200"));
+    assert!(responses.is_empty());
+    testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    let screen = testing::strip_ansi(&picker.render_to_string(3, 60));
+    assert_eq!(
+        screen.lines().next(),
+        Some("  How many lines? 200  (enter a number from 1 to 100)")
+    );
+
+    testing::send(&mut picker, [testing::key(BareKey::Backspace)]);
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Number {
+            index: 1,
+            value: 20
+        }]
+    ));
+    assert!(!picker.is_prompting());
+}
+
+#[test]
+fn test_prompt_graphemes() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("a🇫🇷"));
+    testing::send(&mut picker, [testing::key(BareKey::Backspace)]);
+    assert_eq!(picker.query(), "a");
+
+    picker.set_query("x cafe\u{301} b");
+    testing::send(&mut picker, [testing::key(BareKey::Left)]);
+    testing::send(&mut picker, [testing::key(BareKey::Left)]);
+    testing::send(&mut picker, [testing::key(BareKey::Left)]);
+    testing::send(&mut picker, [testing::key(BareKey::Delete)]);
+    assert_eq!(picker.query(), "x caf b");
+    testing::send(&mut picker, testing::text("e\u{301}"));
+    assert_eq!(picker.query(), "x cafe\u{301} b");
+
+    picker.set_query("x e\u{301}te\u{301}");
+    testing::send(&mut picker, [testing::ctrl(BareKey::Char('w'))]);
+    assert_eq!(picker.query(), "x ");
+}
@@ -0,0 +1,137 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_prefix_first() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.extend([
+        zellij_nucleo::Entry::new("x re", 1),
+        zellij_nucleo::Entry::new("Rename", 2),
+    ]);
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("re"));
+    assert_eq!(testing::results(&picker), ["x re", "Rename"]);
+
+    picker.set_prefix_first(true);
+    assert_eq!(testing::results(&picker), ["Rename", "x re"]);
+}
+
+#[test]
+fn test_prefer_exact_case() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.configure(zellij_nucleo::PickerConfig {
+        case_matching: Some(zellij_nucleo::CaseMatching::Ignore),
+        ..Default::default()
+    });
+    picker.extend([
+        zellij_nucleo::Entry::new("readme", 1),
+        zellij_nucleo::Entry::new("x_README", 2),
+        zellij_nucleo::Entry::new("Readme", 3),
+    ]);
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("README"));
+    assert_eq!(testing::results(&picker), ["Readme", "readme", "x_README"]);
+
+    let searches = picker.metrics().searches;
+    testing::send(
+        &mut picker,
+        [Event::Key(
+            KeyWithModifier::new(BareKey::Char('c')).with_alt_modifier(),
+        )],
+    );
+    assert!(picker.prefer_exact_case());
+    assert_eq!(testing::results(&picker), ["x_README", "Readme", "readme"]);
+    assert_eq!(picker.metrics().searches, searches);
+}
+
+#[test]
+fn test_prefer_exact_case_compares_matched_characters() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+    picker.configure(zellij_nucleo::PickerConfig {
+        case_matching: Some(zellij_nucleo::CaseMatching::Ignore),
+        ..Default::default()
+    });
+    picker.set_prefer_exact_case(true);
+    picker.extend([
+        zellij_nucleo::Entry::new("Aa", 1),
+        zellij_nucleo::Entry::new("aA", 2),
+    ]);
+    picker.enter_search_mode();
+    // both cases appear in the query, but each matched character has to
+    // have the case of the query character it was matched by
+    testing::send(&mut picker, testing::text("aA"));
+    assert_eq!(testing::results(&picker), ["aA", "Aa"]);
+
+    // the combining accent is part of the first grapheme, so the matched
+    // index refers to the second character of the entry
+    picker.clear();
+    picker.extend([
+        zellij_nucleo::Entry::new("é\u{323}X", 1),
+        zellij_nucleo::Entry::new("é\u{323}x", 2),
+    ]);
+    picker.set_query("x");
+    picker.flush();
+    assert_eq!(testing::results(&picker), ["é\u{323}x", "é\u{323}X"]);
+}
+
+#[test]
+fn test_always_show() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("Create new…", 0).with_always_show(true),
+        zellij_nucleo::Entry::new("bar", 1),
+        zellij_nucleo::Entry::new("baz", 2),
+    ]);
+    assert_eq!(testing::results(&picker), ["bar", "baz", "Create new…"]);
+
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("baz"));
+    assert_eq!(testing::results(&picker), ["baz", "Create new…"]);
+
+    testing::send(&mut picker, testing::text("x"));
+    assert_eq!(testing::results(&picker), ["Create new…"]);
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 0, rank: 0 }]
+    ));
+}
+
+#[test]
+fn test_source_priorities_and_limits() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("a.rs", 1).with_source("files"),
+        zellij_nucleo::Entry::new("b.rs", 2).with_source("files"),
+        zellij_nucleo::Entry::new("c.rs", 3).with_source("files"),
+        zellij_nucleo::Entry::new("work", 4).with_source("sessions"),
+        zellij_nucleo::Entry::new("scratch", 5),
+    ]);
+    assert_eq!(
+        testing::results(&picker),
+        ["a.rs", "b.rs", "c.rs", "scratch", "work"]
+    );
+
+    picker.set_source_priority("sessions", 1);
+    picker.set_source_limit("files", Some(2));
+    assert_eq!(
+        testing::results(&picker),
+        ["work", "a.rs", "b.rs", "scratch"]
+    );
+    assert_eq!(picker.match_count(), 4);
+
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("r"));
+    assert_eq!(
+        testing::results(&picker),
+        ["work", "a.rs", "b.rs", "scratch"]
+    );
+    testing::send(&mut picker, testing::text("s"));
+    assert_eq!(testing::results(&picker), ["a.rs", "b.rs"]);
+
+    picker.set_source_limit("files", None);
+    assert_eq!(testing::results(&picker), ["a.rs", "b.rs", "c.rs"]);
+}
@@ -0,0 +1,65 @@
+// the expected results are kept in tests/fixtures/ranking, and can be
+// regenerated by running the tests with UPDATE_GOLDEN=1 (which should
+// only be needed when adding a new profile or new queries)
+
+const QUERIES: &[&str] = &[
+    "", "foo", "rs", "src", "render", "tab", "tab 1", "session", "ca", "md",
+];
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/ranking")
+        .join(name)
+}
+
+fn check_profile(profile: zellij_nucleo::RankingProfile, name: &str) {
+    let entries = std::fs::read_to_string(fixture("entries.txt")).unwrap();
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_ranking_profile(profile);
+    picker.extend(entries.lines().zip(0..).map(|(line, data)| {
+        zellij_nucleo::Entry::new(line.to_string(), data)
+    }));
+    picker.enter_search_mode();
+
+    let mut output = String::new();
+    for query in QUERIES {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        picker.set_query(*query);
+        output.push_str(&format!("> {query}\n"));
+        for result in picker.results() {
+            output.push_str(&format!("{}\n", result.entry.string));
+        }
+    }
+
+    let path = fixture(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, &output).unwrap();
+    }
+    assert_eq!(output, std::fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn test_ranking_profile_v1() {
+    check_profile(zellij_nucleo::RankingProfile::V1, "v1.txt");
+}
+
+#[test]
+fn test_ranking_profile_v2() {
+    check_profile(zellij_nucleo::RankingProfile::V2, "v2.txt");
+}
+
+#[test]
+fn test_ranking_profile_selector() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    assert_eq!(picker.ranking_profile(), zellij_nucleo::RankingProfile::V1);
+    picker.configure(
+        zellij_nucleo::PickerConfig::parse(
+            &[("nucleo_ranking_profile".to_string(), "v2".to_string())]
+                .into(),
+        )
+        .unwrap(),
+    );
+    assert_eq!(picker.ranking_profile(), zellij_nucleo::RankingProfile::V2);
+}
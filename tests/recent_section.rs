@@ -0,0 +1,53 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+#[test]
+fn test_recent_section_moves_entries() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("banana", 2),
+        zellij_nucleo::Entry::new("cherry", 3),
+        zellij_nucleo::Entry::new("date", 4),
+    ]);
+    picker.set_history(vec!["cherry".to_string(), "apple".to_string()]);
+    picker.set_recent_section(2);
+
+    assert_eq!(
+        testing::results(&picker),
+        ["cherry", "apple", "banana", "date"]
+    );
+    assert_eq!(picker.match_count(), 4);
+    let screen = testing::strip_ansi(&picker.render_to_string(8, 20));
+    let lines: Vec<_> = screen.lines().map(str::trim_end).collect();
+    assert_eq!(
+        lines[1..7],
+        ["Recent", "> cherry", "  apple", "All", "  banana", "  date"]
+    );
+
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 3, rank: 3 }]
+    ));
+}
+
+#[test]
+fn test_recent_section_only_without_query() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("apple", 1),
+        zellij_nucleo::Entry::new("apricot", 2),
+        zellij_nucleo::Entry::new("banana", 3),
+    ]);
+    picker.set_history(vec!["apricot".to_string()]);
+    picker.set_recent_section(1);
+    assert_eq!(testing::results(&picker), ["apricot", "apple", "banana"]);
+
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("ap"));
+    assert_eq!(testing::results(&picker), ["apple", "apricot"]);
+    assert_eq!(picker.match_count(), 2);
+}
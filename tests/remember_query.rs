@@ -0,0 +1,67 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn picker() -> zellij_nucleo::Picker<u32> {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.configure(zellij_nucleo::PickerConfig {
+        remember_query: Some(true),
+        start_in_search_mode: Some(true),
+        ..Default::default()
+    });
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+        zellij_nucleo::Entry::new("baz", 3),
+    ]);
+    picker
+}
+
+#[test]
+fn test_remember_query() {
+    let mut picker = picker();
+    testing::send(&mut picker, testing::text("baz"));
+    // switching back to the plugin while it is in use keeps the query
+    testing::send(&mut picker, [Event::Visible(true)]);
+    testing::send(&mut picker, [testing::key(BareKey::Backspace)]);
+    assert_eq!(picker.query(), "ba");
+    testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+
+    testing::send(&mut picker, [Event::Visible(false)]);
+    testing::send(&mut picker, [Event::Visible(true)]);
+    assert_eq!(picker.query(), "ba");
+    assert_eq!(picker.results().count(), 2);
+
+    // the restored query is replaced by typing
+    testing::send(&mut picker, testing::text("f"));
+    assert_eq!(picker.query(), "f");
+    assert_eq!(picker.results().count(), 1);
+}
+
+#[test]
+fn test_remember_query_keep_or_clear() {
+    let mut picker = picker();
+    testing::send(&mut picker, testing::text("ba"));
+    testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    picker.open();
+    testing::send(&mut picker, [testing::key(BareKey::End)]);
+    testing::send(&mut picker, testing::text("r"));
+    assert_eq!(picker.query(), "bar");
+
+    picker.open();
+    testing::send(&mut picker, [testing::key(BareKey::Backspace)]);
+    assert_eq!(picker.query(), "");
+    assert_eq!(picker.results().count(), 3);
+}
+
+#[test]
+fn test_skip_remembered_query() {
+    let mut picker = picker();
+    testing::send(&mut picker, testing::text("ba"));
+    testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+
+    picker.skip_remembered_query();
+    picker.open();
+    assert_eq!(picker.query(), "");
+    picker.open();
+    assert_eq!(picker.query(), "ba");
+}
@@ -0,0 +1,361 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn screen(
+    picker: &mut zellij_nucleo::Picker<u32>,
+    rows: usize,
+) -> Vec<String> {
+    testing::strip_ansi(&picker.render_to_string(rows, 20))
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+#[test]
+fn test_render_query() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+        zellij_nucleo::Entry::new("baz", 3),
+    ]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "> bar", "  baz", "  foo"]
+    );
+
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("fo"));
+    assert_eq!(screen(&mut picker, 4), ["  fo", "> foo"]);
+
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 0, rank: 0 }]
+    ));
+}
+
+#[test]
+fn test_render_groups() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend_group("Tabs", [zellij_nucleo::Entry::new("b", 1)]);
+    picker.extend_group("Sessions", [zellij_nucleo::Entry::new("a", 2)]);
+    picker.extend_group("Tabs", [zellij_nucleo::Entry::new("c", 3)]);
+    assert_eq!(
+        screen(&mut picker, 6),
+        [
+            "  (press / to search)",
+            "Tabs",
+            "> b",
+            "  c",
+            "Sessions",
+            "  a"
+        ]
+    );
+
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    let responses =
+        testing::send(&mut picker, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 1, rank: 2 }]
+    ));
+}
+
+#[test]
+fn test_render_score_bands() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_score_bands(Some(zellij_nucleo::ScoreBands::default()));
+    picker.extend([
+        zellij_nucleo::Entry::new("fxoxoxbxaxr", 1),
+        zellij_nucleo::Entry::new("foo", 2),
+        zellij_nucleo::Entry::new("f_o_o", 3),
+    ]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "> f_o_o", "  foo", "  fxoxoxbxaxr"]
+    );
+
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("foo"));
+    assert_eq!(
+        screen(&mut picker, 6),
+        [
+            "  foo",
+            "> foo",
+            "── medium ──────────",
+            "  f_o_o",
+            "── weak ────────────",
+            "  fxoxoxbxaxr"
+        ]
+    );
+}
+
+#[test]
+fn test_render_anchor_selection() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_anchor_selection(true);
+    picker.extend([
+        zellij_nucleo::Entry::new("m", 1),
+        zellij_nucleo::Entry::new("n", 2),
+        zellij_nucleo::Entry::new("o", 3),
+    ]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  m", "> n", "  o"]
+    );
+
+    picker.extend([
+        zellij_nucleo::Entry::new("a", 4),
+        zellij_nucleo::Entry::new("b", 5),
+    ]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  m", "> n", "  o"]
+    );
+
+    picker.batch(|picker| {
+        picker.retain(|entry| entry.data != 1);
+        picker.extend([zellij_nucleo::Entry::new("c", 6)]);
+    });
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  c", "> n", "  o"]
+    );
+}
+
+#[test]
+fn test_render_source_styles() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("a", 1).with_source("tabs"),
+        zellij_nucleo::Entry::new("b", 2).with_source("sessions"),
+        zellij_nucleo::Entry::new("c", 3),
+    ]);
+    picker.set_source_style(
+        "tabs",
+        zellij_nucleo::TextStyle::fg(zellij_nucleo::Color::Blue),
+    );
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "> ▌ a", "    b", "    c"]
+    );
+
+    picker.clear_source_styles();
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "> a", "  b", "  c"]
+    );
+}
+
+#[test]
+fn test_render_messages() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_status_line(true);
+    picker.enable_multi_select();
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+    ]);
+    testing::send(&mut picker, [testing::key(BareKey::Tab)]);
+    assert_eq!(screen(&mut picker, 4)[1], "  2/2 [normal] (1 entry marked)");
+    testing::send(&mut picker, [testing::key(BareKey::Tab)]);
+    assert_eq!(
+        screen(&mut picker, 4)[1],
+        "  2/2 [normal] (2 entries marked)"
+    );
+
+    picker.set_message_formatter(|message| match *message {
+        zellij_nucleo::Message::Marked { count } => Some(format!(
+            "{} markiert",
+            zellij_nucleo::plural(count, "Eintrag", "Einträge")
+        )),
+        _ => None,
+    });
+    assert_eq!(
+        screen(&mut picker, 4)[1],
+        "  2/2 [normal] (2 Einträge markiert)"
+    );
+}
+
+#[test]
+fn test_render_slice() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_render_slice(Some(2));
+    picker.extend(
+        ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .zip(1..)
+            .map(|(string, data)| zellij_nucleo::Entry::new(string, data)),
+    );
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(
+        screen(&mut picker, 6),
+        ["  (press / to search)", "  a", "  b", "", "> d"]
+    );
+
+    testing::send(&mut picker, [Event::Timer(0.0)]);
+    assert_eq!(
+        screen(&mut picker, 6),
+        ["  (press / to search)", "  a", "  b", "  c", "> d", "  e"]
+    );
+}
+
+#[test]
+fn test_take_needs_redraw() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+    ]);
+    picker.render_to_string(4, 20);
+    assert!(!picker.needs_redraw());
+
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert!(picker.needs_redraw());
+    assert!(picker.take_needs_redraw());
+    assert!(!picker.needs_redraw());
+    assert!(!picker.take_needs_redraw());
+}
+
+#[test]
+fn test_render_fields() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("foo\tbar\tx", 1),
+        zellij_nucleo::Entry::new("quuxxx\tbaz\ty", 2),
+    ]);
+    picker.set_row_renderer(|row| row.render_fields(&[4, 4]));
+    picker.enter_search_mode();
+    testing::send(&mut picker, testing::text("z"));
+    assert_eq!(screen(&mut picker, 4), ["  z", "> quux baz  y"]);
+
+    // the match is highlighted in the second column, even though the
+    // first field was truncated
+    let line = picker.render_to_string(4, 20);
+    assert!(line.contains("quux ba\u{1b}[36mz\u{1b}[0m  y"));
+}
+
+#[test]
+fn test_render_fields_control_chars() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([zellij_nucleo::Entry::new("a\u{1}b\tc", 1)]);
+    picker.set_row_renderer(|row| row.render_fields(&[4]));
+    assert_eq!(screen(&mut picker, 2)[1], "> a\u{2401}b  c");
+
+    // stripped control characters don't take up any columns
+    picker.set_control_chars(zellij_nucleo::ControlChars::Strip);
+    assert_eq!(screen(&mut picker, 2)[1], "> ab   c");
+}
+
+#[test]
+fn test_render_reuses_rows() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.enable_multi_select();
+    picker.extend([
+        zellij_nucleo::Entry::new("a", 1),
+        zellij_nucleo::Entry::new("b", 2),
+        zellij_nucleo::Entry::new("c", 3),
+    ]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "> a", "  b", "  c"]
+    );
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  a", "> b", "  c"]
+    );
+    testing::send(&mut picker, [testing::key(BareKey::Tab)]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  a", " +b", "> c"]
+    );
+    testing::send(&mut picker, [testing::key(BareKey::Up)]);
+    assert_eq!(
+        screen(&mut picker, 4),
+        ["  (press / to search)", "  a", ">+b", "  c"]
+    );
+}
+
+#[test]
+fn test_render_graphemes() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("cafe\u{301} x", 1),
+        zellij_nucleo::Entry::new("\u{1f1ef}\u{1f1f5} jp", 2),
+    ]);
+
+    // the combining accent is part of the matched grapheme, even though
+    // every grapheme starts with an ascii character
+    picker.set_query("e x");
+    let result = picker.selected_result().unwrap();
+    assert_eq!(result.indices, [3, 5]);
+    let screen = picker.render_to_string(4, 20);
+    assert!(screen.contains("\u{1b}[36me\u{301}\u{1b}[0m"));
+    assert!(screen.contains("\u{1b}[36mx\u{1b}[0m"));
+
+    // the flag is a single grapheme made of two characters
+    picker.set_query("jp");
+    let result = picker.selected_result().unwrap();
+    assert_eq!(result.indices, [2, 3]);
+    let screen = picker.render_to_string(4, 20);
+    assert!(screen.contains("\u{1b}[33m\u{1f1ef}\u{1f1f5}\u{1b}[0m"));
+    assert!(screen.contains("\u{1b}[36mj\u{1b}[0m\u{1b}[36mp\u{1b}[0m"));
+}
+
+#[test]
+fn test_render_slice_reuses_rows() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_render_slice(Some(2));
+    picker.extend(
+        ["a", "b", "c", "d", "e"]
+            .into_iter()
+            .zip(1..)
+            .map(|(string, data)| zellij_nucleo::Entry::new(string, data)),
+    );
+    screen(&mut picker, 6);
+    testing::send(&mut picker, [Event::Timer(0.0)]);
+    assert_eq!(
+        screen(&mut picker, 6),
+        ["  (press / to search)", "> a", "  b", "  c", "  d", "  e"]
+    );
+
+    // only the rows whose selection state changed need rendering again,
+    // so moving the selection doesn't blank out the rest of the list
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    testing::send(&mut picker, [testing::key(BareKey::Down)]);
+    assert_eq!(
+        screen(&mut picker, 6),
+        ["  (press / to search)", "  a", "  b", "  c", "> d", "  e"]
+    );
+    assert!(!picker.needs_redraw());
+}
+
+#[test]
+fn test_render_keybinding_narrow_pane() {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.extend([
+        zellij_nucleo::Entry::new("a rather long entry", 1)
+            .with_keybinding("x")
+            .with_current(true),
+        zellij_nucleo::Entry::new("short", 2).with_keybinding("y"),
+    ]);
+    picker.set_tiebreak(zellij_nucleo::Tiebreak::SourceOrder);
+
+    // the hint doesn't fit next to the current marker and the truncated
+    // entry, so it is dropped
+    let screen = testing::strip_ansi(&picker.render_to_string(3, 8));
+    let lines: Vec<_> = screen.lines().map(str::trim_end).collect();
+    assert_eq!(lines[1..], ["> ●  [...]", "  short"]);
+
+    let screen = testing::strip_ansi(&picker.render_to_string(3, 20));
+    let lines: Vec<_> = screen.lines().map(str::trim_end).collect();
+    assert_eq!(lines[1..], ["> ● a rather [...] x", "  short            y"]);
+}
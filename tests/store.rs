@@ -0,0 +1,32 @@
+use zellij_nucleo::testing;
+use zellij_tile::prelude::*;
+
+fn picker(store: &testing::MemoryStore) -> zellij_nucleo::Picker<u32> {
+    let mut picker = zellij_nucleo::Picker::<u32>::default();
+    picker.set_store(store.clone());
+    picker.extend([
+        zellij_nucleo::Entry::new("foo", 1),
+        zellij_nucleo::Entry::new("bar", 2),
+        zellij_nucleo::Entry::new("baz", 3),
+    ]);
+    picker
+}
+
+#[test]
+fn test_store() {
+    let store = testing::MemoryStore::default();
+
+    let mut first = picker(&store);
+    first.pin("foo");
+    testing::send(&mut first, [testing::key(BareKey::Down)]);
+    let responses = testing::send(&mut first, [testing::key(BareKey::Enter)]);
+    assert!(matches!(
+        responses[..],
+        [zellij_nucleo::Response::Select { index: 1, .. }]
+    ));
+
+    let second = picker(&store);
+    assert_eq!(second.history(), ["bar"]);
+    assert_eq!(second.pinned().collect::<Vec<_>>(), ["foo"]);
+    assert_eq!(second.usage()["bar"].count, 1);
+}